@@ -0,0 +1,365 @@
+//! Background music that crossfades between tracks.
+//!
+//! Music isn't spawned directly. Instead, hand a [`Playlist`] to the
+//! [`MusicDirector`], which fades out whatever is playing, fades in the new
+//! tracks one after another, and ducks the music while something like the
+//! pause menu is open. The next track starts fading in before the current one
+//! ends, so that the two overlap for the crossfade.
+
+use std::{collections::HashSet, time::Duration};
+
+#[cfg(not(target_family = "wasm"))]
+use bevy::asset::LoadedFolder;
+use bevy::{audio::PlaybackMode, prelude::*};
+use rand::seq::SliceRandom;
+
+use crate::{
+    AppSystems,
+    asset_tracking::LoadResource,
+    audio::{Music, VolumeScale},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.load_resource::<MusicAssets>();
+    app.init_resource::<MusicDirector>();
+
+    #[cfg(not(target_family = "wasm"))]
+    app.add_systems(
+        Update,
+        collect_music_tracks
+            .run_if(resource_added::<MusicAssets>)
+            .before(start_next_track),
+    );
+    app.add_systems(
+        Update,
+        (start_next_track, fade_music)
+            .chain()
+            .in_set(AppSystems::Update),
+    );
+}
+
+/// The folder the music is loaded from.
+const MUSIC_FOLDER: &str = "audio/music";
+
+/// The files in the music folder, as folders can't be loaded on the web. Add
+/// new tracks here to include them in the web build.
+#[cfg(target_family = "wasm")]
+const WEB_MUSIC_FILES: &[&str] = &["Fluffing A Duck.ogg", "Monkeys Spinning Monkeys.ogg"];
+
+/// Every track in the music folder.
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct MusicAssets {
+    /// The loaded music folder, whose tracks are collected into `tracks`.
+    #[cfg(not(target_family = "wasm"))]
+    #[dependency]
+    folder: Handle<LoadedFolder>,
+    #[dependency]
+    tracks: Vec<Handle<AudioSource>>,
+}
+
+impl FromWorld for MusicAssets {
+    #[cfg(not(target_family = "wasm"))]
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            folder: assets.load_folder(MUSIC_FOLDER),
+            tracks: Vec::new(),
+        }
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            tracks: WEB_MUSIC_FILES
+                .iter()
+                .map(|file| assets.load(format!("{MUSIC_FOLDER}/{file}")))
+                .collect(),
+        }
+    }
+}
+
+impl MusicAssets {
+    /// A playlist of every track in the music folder.
+    pub fn playlist(&self) -> Playlist {
+        Playlist::new(self.tracks.clone())
+    }
+}
+
+/// Tracks that are played one after another, repeating from the start after
+/// the last one. A playlist with a single track loops it seamlessly.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub struct Playlist {
+    tracks: Vec<Handle<AudioSource>>,
+    shuffle: bool,
+}
+
+impl Playlist {
+    pub fn new(tracks: Vec<Handle<AudioSource>>) -> Self {
+        Self {
+            tracks,
+            shuffle: false,
+        }
+    }
+
+    /// A playlist that loops a single track.
+    pub fn single(track: Handle<AudioSource>) -> Self {
+        Self::new(vec![track])
+    }
+
+    /// Play the tracks in a random order, reshuffled every time the playlist repeats.
+    pub fn shuffled(mut self) -> Self {
+        self.shuffle = true;
+        self
+    }
+}
+
+/// Why music is ducked. The music stays ducked while there is any reason for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum DuckReason {
+    Pause,
+}
+
+/// Plays [`Playlist`]s, crossfading between tracks.
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct MusicDirector {
+    /// How long one track takes to fade out while the next one fades in.
+    pub crossfade: Duration,
+    /// How loud ducked music is, relative to its normal volume.
+    pub ducked_volume: f32,
+    /// How long the music takes to duck, or to return to normal.
+    pub duck_duration: Duration,
+    playlist: Option<Playlist>,
+    /// The order to play the playlist's tracks in, as indices into it.
+    order: Vec<usize>,
+    /// The index into `order` of the current track.
+    position: usize,
+    /// Whether the current track should be replaced by the one at `position`.
+    track_changed: bool,
+    ducking: HashSet<DuckReason>,
+    /// The current ducking volume, moving towards `ducked_volume` or `1.0`.
+    duck_scale: f32,
+}
+
+impl Default for MusicDirector {
+    fn default() -> Self {
+        Self {
+            crossfade: Duration::from_secs(2),
+            ducked_volume: 0.3,
+            duck_duration: Duration::from_millis(300),
+            playlist: None,
+            order: Vec::new(),
+            position: 0,
+            track_changed: false,
+            ducking: HashSet::new(),
+            duck_scale: 1.0,
+        }
+    }
+}
+
+impl MusicDirector {
+    /// Crossfade to `playlist`. Does nothing if it is already playing.
+    pub fn play(&mut self, playlist: Playlist) {
+        if self.playlist.as_ref() == Some(&playlist) {
+            return;
+        }
+        self.order = (0..playlist.tracks.len()).collect();
+        if playlist.shuffle {
+            self.order.shuffle(&mut rand::rng());
+        }
+        self.position = 0;
+        self.playlist = Some(playlist);
+        self.track_changed = true;
+    }
+
+    /// Fade out the music.
+    pub fn stop(&mut self) {
+        self.playlist = None;
+        self.track_changed = true;
+    }
+
+    /// Crossfade to the next track in the playlist.
+    pub fn skip(&mut self) {
+        let Some(playlist) = &self.playlist else {
+            return;
+        };
+        self.position += 1;
+        if self.position >= self.order.len() {
+            self.position = 0;
+            if playlist.shuffle {
+                self.order.shuffle(&mut rand::rng());
+            }
+        }
+        self.track_changed = true;
+    }
+
+    /// Duck the music until [`Self::unduck`] is called with the same reason.
+    pub fn duck(&mut self, reason: DuckReason) {
+        self.ducking.insert(reason);
+    }
+
+    pub fn unduck(&mut self, reason: DuckReason) {
+        self.ducking.remove(&reason);
+    }
+
+    fn current_track(&self) -> Option<Handle<AudioSource>> {
+        let playlist = self.playlist.as_ref()?;
+        let index = *self.order.get(self.position)?;
+        Some(playlist.tracks[index].clone())
+    }
+
+    /// Whether the current track should loop instead of moving on to the next one.
+    fn loops_track(&self) -> bool {
+        self.playlist
+            .as_ref()
+            .is_some_and(|playlist| playlist.tracks.len() == 1)
+    }
+}
+
+/// A music track started by the [`MusicDirector`].
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+struct MusicTrack {
+    /// How far the track has faded in, from `0.0` to `1.0`.
+    fade: f32,
+    /// Whether the track is fading out, to be despawned once silent.
+    fading_out: bool,
+    /// How long the track is, once its audio has been read.
+    length: Option<Duration>,
+}
+
+/// Collect the audio files in the loaded music folder into the playlist's tracks.
+#[cfg(not(target_family = "wasm"))]
+fn collect_music_tracks(
+    mut music_assets: ResMut<MusicAssets>,
+    folders: Res<Assets<LoadedFolder>>,
+) {
+    let Some(folder) = folders.get(&music_assets.folder) else {
+        return;
+    };
+    let mut tracks = folder
+        .handles
+        .iter()
+        .filter_map(|handle| handle.clone().try_typed::<AudioSource>().ok())
+        .collect::<Vec<_>>();
+    // Folders are listed in no particular order.
+    tracks.sort_by_key(|track| track.path().map(ToString::to_string));
+    music_assets.tracks = tracks;
+}
+
+fn start_next_track(
+    mut commands: Commands,
+    mut director: ResMut<MusicDirector>,
+    sources: Res<Assets<AudioSource>>,
+    mut tracks: Query<(&mut MusicTrack, &AudioPlayer, Option<&AudioSink>)>,
+) {
+    // Start the next track a crossfade before the current one ends.
+    let mut ending = false;
+    for (mut track, player, sink) in &mut tracks {
+        if track.fading_out || director.loops_track() {
+            continue;
+        }
+        let Some(sink) = sink else {
+            continue;
+        };
+        if track.length.is_none() {
+            track.length = sources
+                .get(&player.0)
+                .and_then(|source| ogg_length(&source.bytes));
+        }
+        let remaining = track
+            .length
+            .map(|length| length.saturating_sub(sink.position()));
+        ending |= sink.empty()
+            || remaining.is_some_and(|remaining| remaining <= director.crossfade);
+    }
+    if ending {
+        director.skip();
+    }
+    if !director.track_changed {
+        return;
+    }
+    director.track_changed = false;
+
+    for (mut track, _, _) in &mut tracks {
+        track.fading_out = true;
+    }
+
+    let Some(handle) = director.current_track() else {
+        return;
+    };
+    let mode = if director.loops_track() {
+        PlaybackMode::Loop
+    } else {
+        PlaybackMode::Once
+    };
+    commands.spawn((
+        Name::new("Music Track"),
+        AudioPlayer(handle),
+        PlaybackSettings { mode, ..default() },
+        Music,
+        MusicTrack::default(),
+        VolumeScale(0.0),
+    ));
+}
+
+fn fade_music(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut director: ResMut<MusicDirector>,
+    mut tracks: Query<(Entity, &mut MusicTrack, &mut VolumeScale)>,
+) {
+    let duck_target = if director.ducking.is_empty() {
+        1.0
+    } else {
+        director.ducked_volume
+    };
+    let duck_step = step(time.delta(), director.duck_duration);
+    director.duck_scale = move_towards(director.duck_scale, duck_target, duck_step);
+
+    let fade_step = step(time.delta(), director.crossfade);
+    for (entity, mut track, mut scale) in &mut tracks {
+        let target = if track.fading_out { 0.0 } else { 1.0 };
+        track.fade = move_towards(track.fade, target, fade_step);
+        if track.fading_out && track.fade == 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        scale.set_if_neq(VolumeScale(track.fade * director.duck_scale));
+    }
+}
+
+/// The length of an Ogg Vorbis file, from its sample rate and the sample
+/// position at the end of its last page. `None` if it isn't Ogg Vorbis.
+fn ogg_length(bytes: &[u8]) -> Option<Duration> {
+    const VORBIS_ID: &[u8] = b"\x01vorbis";
+    const OGG_PAGE: &[u8] = b"OggS";
+    let id_start = bytes
+        .windows(VORBIS_ID.len())
+        .position(|window| window == VORBIS_ID)?;
+    // The sample rate follows the version and the channel count.
+    let rate_start = id_start + VORBIS_ID.len() + 5;
+    let rate = u32::from_le_bytes(bytes.get(rate_start..rate_start + 4)?.try_into().ok()?);
+    let page_start = bytes
+        .windows(OGG_PAGE.len())
+        .rposition(|window| window == OGG_PAGE)?;
+    // The granule position follows the capture pattern, version and header type.
+    let granule_start = page_start + 6;
+    let samples = u64::from_le_bytes(bytes.get(granule_start..granule_start + 8)?.try_into().ok()?);
+    (rate > 0).then(|| Duration::from_secs_f64(samples as f64 / f64::from(rate)))
+}
+
+/// How far a fade that takes `duration` moves in `delta`, from `0.0` to `1.0`.
+fn step(delta: Duration, duration: Duration) -> f32 {
+    if duration.is_zero() {
+        1.0
+    } else {
+        delta.as_secs_f32() / duration.as_secs_f32()
+    }
+}
+
+fn move_towards(current: f32, target: f32, max_step: f32) -> f32 {
+    current + (target - current).clamp(-max_step, max_step)
+}
@@ -0,0 +1,270 @@
+//! Audio categories, their volumes, spatial audio, and background music.
+
+pub mod director;
+
+use bevy::{
+    audio::{AudioSinkPlayback, Volume},
+    ecs::component::Mutable,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ChannelVolumes>();
+
+    app.add_observer(apply_volume_to_new_sink::<AudioSink>);
+    app.add_observer(apply_volume_to_new_sink::<SpatialAudioSink>);
+    // Run after `Update`, so that volume changes made this frame are heard this frame.
+    app.add_systems(
+        PostUpdate,
+        (apply_volume::<AudioSink>, apply_volume::<SpatialAudioSink>),
+    );
+
+    app.add_plugins(director::plugin);
+}
+
+/// How many world units (pixels) make up one unit of distance for spatial audio.
+///
+/// Spatial sounds play at full volume within this distance of the [`SpatialListener`],
+/// and fall off with the inverse square of the distance beyond it.
+pub const SPATIAL_AUDIO_UNIT: f32 = 100.0;
+
+/// The distance between the left and right ears of the [`SpatialListener`], in world units.
+/// Sounds further to one side than half of this are fully panned to that side.
+pub const LISTENER_EAR_GAP: f32 = 400.0;
+
+/// A category of audio whose volume can be set independently in the settings.
+///
+/// This is required by the [`Music`], [`SoundEffect`], [`UiSound`] and [`Ambient`] markers,
+/// so it doesn't need to be added by hand.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component)]
+pub enum AudioChannel {
+    Music,
+    SoundEffect,
+    Ui,
+    Ambient,
+}
+
+impl AudioChannel {
+    /// A human-readable name for the settings menu.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Music => "Music",
+            Self::SoundEffect => "Sound Effects",
+            Self::Ui => "Interface",
+            Self::Ambient => "Ambient",
+        }
+    }
+}
+
+/// The volume of a single [`AudioChannel`], on top of the [`GlobalVolume`].
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChannelVolume {
+    pub volume: f32,
+    pub muted: bool,
+}
+
+impl Default for ChannelVolume {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl ChannelVolume {
+    /// The volume to play at, taking muting into account.
+    pub fn effective(&self) -> Volume {
+        if self.muted {
+            Volume::SILENT
+        } else {
+            Volume::Linear(self.volume)
+        }
+    }
+}
+
+/// The volume of every [`AudioChannel`].
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct ChannelVolumes {
+    pub music: ChannelVolume,
+    pub sound_effects: ChannelVolume,
+    pub ui: ChannelVolume,
+    pub ambient: ChannelVolume,
+}
+
+impl ChannelVolumes {
+    pub fn get(&self, channel: AudioChannel) -> &ChannelVolume {
+        match channel {
+            AudioChannel::Music => &self.music,
+            AudioChannel::SoundEffect => &self.sound_effects,
+            AudioChannel::Ui => &self.ui,
+            AudioChannel::Ambient => &self.ambient,
+        }
+    }
+
+    pub fn get_mut(&mut self, channel: AudioChannel) -> &mut ChannelVolume {
+        match channel {
+            AudioChannel::Music => &mut self.music,
+            AudioChannel::SoundEffect => &mut self.sound_effects,
+            AudioChannel::Ui => &mut self.ui,
+            AudioChannel::Ambient => &mut self.ambient,
+        }
+    }
+}
+
+/// An organizational marker component that should be added to a spawned [`AudioPlayer`] if it's in the
+/// general "music" category (e.g. global background music, soundtrack).
+///
+/// This can then be used to query for and operate on sounds in that category.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+#[require(AudioChannel = AudioChannel::Music)]
+pub struct Music;
+
+/// A music audio instance.
+pub fn music(handle: Handle<AudioSource>) -> impl Bundle {
+    (AudioPlayer(handle), PlaybackSettings::LOOP, Music)
+}
+
+/// An organizational marker component that should be added to a spawned [`AudioPlayer`] if it's in the
+/// general "sound effect" category (e.g. footsteps, the sound of a magic spell, a door opening).
+///
+/// This can then be used to query for and operate on sounds in that category.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+#[require(AudioChannel = AudioChannel::SoundEffect)]
+pub struct SoundEffect;
+
+/// A sound effect audio instance.
+pub fn sound_effect(handle: Handle<AudioSource>) -> impl Bundle {
+    (AudioPlayer(handle), PlaybackSettings::DESPAWN, SoundEffect)
+}
+
+/// An organizational marker component that should be added to a spawned [`AudioPlayer`] if it's in the
+/// general "user interface" category (e.g. button hover and click sounds).
+///
+/// This can then be used to query for and operate on sounds in that category.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+#[require(AudioChannel = AudioChannel::Ui)]
+pub struct UiSound;
+
+/// A user interface sound instance.
+pub fn ui_sound(handle: Handle<AudioSource>) -> impl Bundle {
+    (AudioPlayer(handle), PlaybackSettings::DESPAWN, UiSound)
+}
+
+/// An organizational marker component that should be added to a spawned [`AudioPlayer`] if it's in the
+/// general "ambient" category (e.g. wind, birdsong, a crackling fire).
+///
+/// This can then be used to query for and operate on sounds in that category.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+#[require(AudioChannel = AudioChannel::Ambient)]
+pub struct Ambient;
+
+/// A sound effect that is heard from `translation` in the world, getting quieter
+/// with distance from the [`SpatialListener`] and panning to its side.
+pub fn spatial_sound_effect(handle: Handle<AudioSource>, translation: Vec3) -> impl Bundle {
+    (
+        AudioPlayer(handle),
+        PlaybackSettings::DESPAWN.with_spatial(true),
+        SoundEffect,
+        Transform::from_translation(translation),
+    )
+}
+
+/// A looping ambient audio instance that is heard from `translation` in the world,
+/// such as a waterfall or a campfire. See [`spatial_sound_effect`].
+pub fn spatial_ambient(handle: Handle<AudioSource>, translation: Vec3) -> impl Bundle {
+    (
+        AudioPlayer(handle),
+        PlaybackSettings::LOOP.with_spatial(true),
+        Ambient,
+        Transform::from_translation(translation),
+    )
+}
+
+/// A linear factor applied to the volume of a single audio entity, on top of its
+/// [`PlaybackSettings`] and channel volume. Use this for volume that changes over
+/// time, such as fades.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct VolumeScale(pub f32);
+
+impl Default for VolumeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// The volume an audio entity should play at.
+fn volume_of(
+    global_volume: &GlobalVolume,
+    channel_volumes: &ChannelVolumes,
+    channel: Option<&AudioChannel>,
+    scale: Option<&VolumeScale>,
+    playback: &PlaybackSettings,
+) -> Volume {
+    let channel_volume = channel.map_or(Volume::Linear(1.0), |channel| {
+        channel_volumes.get(*channel).effective()
+    });
+    let scale = Volume::Linear(scale.map_or(1.0, |scale| scale.0));
+    global_volume.volume * channel_volume * scale * playback.volume
+}
+
+/// Bevy only applies [`GlobalVolume`] to new audio entities, so this observer applies the channel volume too.
+fn apply_volume_to_new_sink<S: Component<Mutability = Mutable> + AudioSinkPlayback>(
+    add: On<Add, S>,
+    global_volume: Res<GlobalVolume>,
+    channel_volumes: Res<ChannelVolumes>,
+    mut audio_query: Query<(
+        &PlaybackSettings,
+        Option<&AudioChannel>,
+        Option<&VolumeScale>,
+        &mut S,
+    )>,
+) {
+    let Ok((playback, channel, scale, mut sink)) = audio_query.get_mut(add.entity) else {
+        return;
+    };
+    sink.set_volume(volume_of(
+        &global_volume,
+        &channel_volumes,
+        channel,
+        scale,
+        playback,
+    ));
+}
+
+/// [`GlobalVolume`], [`ChannelVolumes`] and [`VolumeScale`] don't apply to already-running audio entities,
+/// so this system will update them.
+fn apply_volume<S: Component<Mutability = Mutable> + AudioSinkPlayback>(
+    global_volume: Res<GlobalVolume>,
+    channel_volumes: Res<ChannelVolumes>,
+    mut audio_query: Query<(
+        &PlaybackSettings,
+        Option<&AudioChannel>,
+        Option<Ref<VolumeScale>>,
+        &mut S,
+    )>,
+) {
+    let volumes_changed = global_volume.is_changed() || channel_volumes.is_changed();
+    for (playback, channel, scale, mut sink) in &mut audio_query {
+        if !volumes_changed && !scale.as_ref().is_some_and(Ref::is_changed) {
+            continue;
+        }
+        sink.set_volume(volume_of(
+            &global_volume,
+            &channel_volumes,
+            channel,
+            scale.as_deref(),
+            playback,
+        ));
+    }
+}
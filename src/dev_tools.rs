@@ -1,10 +1,11 @@
 //! Development tools for the game. This plugin is only enabled in dev builds.
 
-use bevy::{
-    dev_tools::states::log_transitions, input::common_conditions::input_just_pressed, prelude::*,
-};
+use bevy::{dev_tools::states::log_transitions, prelude::*};
 
-use crate::screens::Screen;
+use crate::{
+    input::{InputAction, action_just_pressed},
+    screens::Screen,
+};
 
 pub(super) fn plugin(app: &mut App) {
     // Log `Screen` state transitions.
@@ -13,12 +14,10 @@ pub(super) fn plugin(app: &mut App) {
     // Toggle the debug overlay for UI.
     app.add_systems(
         Update,
-        toggle_debug_ui.run_if(input_just_pressed(TOGGLE_KEY)),
+        toggle_debug_ui.run_if(action_just_pressed(InputAction::ToggleDebugUi)),
     );
 }
 
-const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
-
 fn toggle_debug_ui(mut options: ResMut<UiDebugOptions>) {
     options.toggle();
 }
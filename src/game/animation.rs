@@ -1,294 +1,229 @@
 //! Player sprite animation.
-//! This is based on multiple examples and may be very different for your game.
-//! - [Sprite flipping](https://github.com/bevyengine/bevy/blob/latest/examples/2d/sprite_flipping.rs)
-//! - [Sprite animation](https://github.com/bevyengine/bevy/blob/latest/examples/2d/sprite_animation.rs)
-//! - [Timers](https://github.com/bevyengine/bevy/blob/latest/examples/time/timers.rs)
+//!
+//! The clips themselves are described in `assets/animations/player.anim.ron`
+//! and played by a [`SpriteAnimator`]. This module only picks which clip to
+//! play, in two layers:
+//! - The base layer loops idle or run clips, following the player's movement.
+//! - One-shot clips like `attack`, `hurt` or `death` are started with
+//!   [`PlayOneShot`] and interrupt the base layer until they finish. A one-shot
+//!   clip can only be interrupted by one with a higher priority, and can lock
+//!   the player's movement while it plays.
+//!
+//! Taking [`Damage`] plays `hurt`, and being [`Defeated`] plays `death`.
 
 use bevy::prelude::*;
-use rand::prelude::*;
-use std::time::Duration;
 
 use crate::{
     AppSystems, PausableSystems,
-    audio::sound_effect,
-    game::{movement::MovementController, player::PlayerAssets},
+    game::{
+        combat::{Damage, Defeated},
+        facing::{CompassDirection, Facing, update_facing},
+        movement::MovementController,
+        player::PlayerAssets,
+        sprite_animation::{SpriteAnimations, SpriteAnimator},
+    },
 };
 
 pub(super) fn plugin(app: &mut App) {
-    // Animate and play sound effects based on controls.
+    app.add_observer(play_one_shot);
+    app.add_observer(play_hurt_animation);
+    app.add_observer(play_death_animation);
+
+    // Animate based on controls.
     app.add_systems(
         Update,
-        (
-            update_animation_timer.in_set(AppSystems::TickTimers),
-            (
-                update_animation_movement,
-                update_animation_atlas,
-                trigger_step_sound_effect,
-            )
-                .chain()
-                .run_if(resource_exists::<PlayerAssets>)
-                .in_set(AppSystems::Update),
-        )
+        (update_animation_movement, play_base_animation_clip)
+            .chain()
+            .after(update_facing)
+            .run_if(resource_exists::<PlayerAssets>)
+            .in_set(AppSystems::Update)
             .in_set(PausableSystems),
     );
 }
 
-/// Update the sprite direction and animation state (idling/walking).
-fn update_animation_movement(mut player_query: Query<(&MovementController, &mut PlayerAnimation)>) {
-    for (controller, mut animation) in &mut player_query {
-        let dx = controller.intent.x;
-        let dy = controller.intent.y;
-
-        let mut animation_state = PlayerAnimationState::Idling;
-        let mut animation_direction = animation.direction; // Default direction
+/// Play a one-shot clip, e.g. `attack`, on a character with a
+/// [`PlayerAnimation`], in the direction it is [`Facing`].
+///
+/// Does nothing if there is no such clip, or if a one-shot clip with the same
+/// or a higher priority is already playing.
+#[derive(EntityEvent, Debug, Clone)]
+pub struct PlayOneShot {
+    pub entity: Entity,
+    /// The clip name without its direction, e.g. `attack` for `attack_east`.
+    pub animation: String,
+}
 
-        // Check if player is moving
-        if controller.intent != Vec2::ZERO {
-            animation_state = PlayerAnimationState::Running;
+/// Update the base animation (idling/running) and its direction, and end
+/// one-shot clips that have finished.
+fn update_animation_movement(
+    mut player_query: Query<(
+        &mut MovementController,
+        &Facing,
+        &mut PlayerAnimation,
+        &SpriteAnimator,
+    )>,
+) {
+    for (mut controller, facing, mut animation, animator) in &mut player_query {
+        if animation
+            .one_shot
+            .as_ref()
+            .is_some_and(|one_shot| !one_shot.is_playing(animator))
+        {
+            animation.one_shot = None;
+        }
 
-            // Determine direction based on movement
-            // For diagonal movement, prioritize the axis with greater magnitude
-            if dx.abs() > dy.abs() {
-                // Horizontal movement takes priority
-                if dx > 0.0 {
-                    animation_direction = PlayerDirection::East;
-                } else {
-                    animation_direction = PlayerDirection::West;
-                }
-            } else {
-                // Vertical movement takes priority
-                if dy > 0.0 {
-                    animation_direction = PlayerDirection::North;
-                } else {
-                    animation_direction = PlayerDirection::South;
-                }
-            }
+        let base = if controller.intent == Vec2::ZERO {
+            BaseAnimation::Idling
+        } else {
+            BaseAnimation::Running
+        };
+        if animation.base != base {
+            animation.base = base;
+        }
+        if animation.direction != facing.direction {
+            animation.direction = facing.direction;
         }
 
-        animation.update_state_and_direction(animation_state, animation_direction);
+        // Only touch the controller when the clip's lock changes, so that
+        // locks from anything else are left alone.
+        let locked = animation
+            .one_shot
+            .as_ref()
+            .is_some_and(|one_shot| one_shot.locks_movement);
+        if animation.locked_by_animation != locked {
+            animation.locked_by_animation = locked;
+            controller.locked = locked;
+        }
     }
 }
 
-/// Update the animation timer.
-fn update_animation_timer(time: Res<Time>, mut query: Query<&mut PlayerAnimation>) {
-    for mut animation in &mut query {
-        animation.update_timer(time.delta());
+/// Play the base clip matching the player's animation state and direction, or
+/// the closest one there is a spritesheet for, unless a one-shot clip is playing.
+fn play_base_animation_clip(
+    animations: Res<Assets<SpriteAnimations>>,
+    mut query: Query<(&PlayerAnimation, &mut SpriteAnimator), Changed<PlayerAnimation>>,
+) {
+    for (animation, mut animator) in &mut query {
+        if animation.one_shot.is_some() {
+            continue;
+        }
+        let Some((clip, flip_x)) = animations
+            .get(animator.animations())
+            .and_then(|animations| {
+                animations.directional_clip(animation.base.clip_prefix(), animation.direction)
+            })
+        else {
+            continue;
+        };
+        animator.play(&clip);
+        animator.set_flip_x(flip_x);
     }
 }
 
-/// Update the texture atlas to reflect changes in the animation.
-fn update_animation_atlas(
-    player_assets: Res<PlayerAssets>,
-    mut query: Query<(&PlayerAnimation, &mut Sprite)>,
+fn play_one_shot(
+    play: On<PlayOneShot>,
+    animations: Res<Assets<SpriteAnimations>>,
+    mut query: Query<(&Facing, &mut PlayerAnimation, &mut SpriteAnimator)>,
 ) {
-    for (animation, mut sprite) in &mut query {
-        // Update the frame index within the spritesheet
-        if let Some(atlas) = sprite.texture_atlas.as_mut() {
-            atlas.index = animation.get_atlas_index();
-        }
-
-        if animation.changed() {
-            // Calculate the spritesheet index based on state and direction
-            let spritesheet_index = match (animation.state, animation.direction) {
-                (PlayerAnimationState::Idling, PlayerDirection::East) => 0,
-                (PlayerAnimationState::Idling, PlayerDirection::North) => 1,
-                (PlayerAnimationState::Idling, PlayerDirection::South) => 2,
-                (PlayerAnimationState::Idling, PlayerDirection::West) => 3,
-                (PlayerAnimationState::Running, PlayerDirection::East) => 4,
-                (PlayerAnimationState::Running, PlayerDirection::North) => 5,
-                (PlayerAnimationState::Running, PlayerDirection::South) => 6,
-                (PlayerAnimationState::Running, PlayerDirection::West) => 7,
-            };
+    let Ok((facing, mut animation, mut animator)) = query.get_mut(play.entity) else {
+        return;
+    };
+    let Some(animations) = animations.get(animator.animations()) else {
+        return;
+    };
+    let Some((clip_name, flip_x)) =
+        animations.directional_clip(&play.animation, facing.direction)
+    else {
+        return;
+    };
+    let Some(clip) = animations.clip(&clip_name) else {
+        return;
+    };
+    if animation.one_shot.as_ref().is_some_and(|one_shot| {
+        one_shot.is_playing(&animator) && one_shot.priority >= clip.priority
+    }) {
+        return;
+    }
+
+    animator.play(&clip_name);
+    // The same clip may have been left finished by the previous one-shot.
+    animator.restart();
+    animator.set_flip_x(flip_x);
+    animation.one_shot = Some(OneShot {
+        clip: clip_name,
+        priority: clip.priority,
+        locks_movement: clip.locks_movement,
+    });
+}
 
-            // Update the texture to use the correct spritesheet
-            sprite.image = player_assets.spritesheets[spritesheet_index].clone();
-        }
-    }
+fn play_hurt_animation(damage: On<Damage>, mut commands: Commands) {
+    commands.trigger(PlayOneShot {
+        entity: damage.entity,
+        animation: "hurt".to_string(),
+    });
 }
 
-/// If the player is moving, play a step sound effect synchronized with the
-/// animation.
-fn trigger_step_sound_effect(
-    mut commands: Commands,
-    player_assets: Res<PlayerAssets>,
-    mut step_query: Query<&PlayerAnimation>,
-) {
-    for animation in &mut step_query {
-        if animation.state == PlayerAnimationState::Running
-            && animation.changed()
-            && (animation.frame == 2 || animation.frame == 5)
-        {
-            let rng = &mut rand::rng();
-            let random_step = player_assets.sounds.choose(rng).unwrap().clone();
-            commands.spawn(sound_effect(random_step));
-        }
-    }
+fn play_death_animation(defeated: On<Defeated>, mut commands: Commands) {
+    commands.trigger(PlayOneShot {
+        entity: defeated.entity,
+        animation: "death".to_string(),
+    });
 }
 
+/// The looping clips played when no one-shot clip is.
 #[derive(Reflect, PartialEq, Copy, Clone)]
-pub enum PlayerAnimationState {
+pub enum BaseAnimation {
     Idling,
     Running,
 }
 
-#[derive(Reflect, PartialEq, Copy, Clone)]
-pub enum PlayerDirection {
-    North,
-    South,
-    East,
-    West,
+impl BaseAnimation {
+    /// The first part of the clip names for this animation.
+    fn clip_prefix(&self) -> &'static str {
+        match self {
+            Self::Idling => "idle",
+            Self::Running => "run",
+        }
+    }
+}
+
+/// A one-shot clip playing over the base animation.
+#[derive(Reflect, PartialEq, Clone)]
+struct OneShot {
+    /// The full name of the clip, e.g. `attack_east`.
+    clip: String,
+    priority: u32,
+    locks_movement: bool,
+}
+
+impl OneShot {
+    /// Whether the clip is still playing. It is over once it has finished, or
+    /// if something else has replaced it on the animator.
+    fn is_playing(&self, animator: &SpriteAnimator) -> bool {
+        animator.clip() == self.clip && !animator.is_finished()
+    }
 }
 
-/// Component that tracks player's animation state.
-/// It is tightly bound to the texture atlas we use.
+/// Component that tracks player's animation state, which selects the clip
+/// played by the player's [`SpriteAnimator`].
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct PlayerAnimation {
-    timer: Timer,
-    frame: usize,
-    state: PlayerAnimationState,
-    direction: PlayerDirection,
+    base: BaseAnimation,
+    /// The direction of the base clip, which follows the player's [`Facing`].
+    direction: CompassDirection,
+    one_shot: Option<OneShot>,
+    /// Whether the one-shot clip has locked the player's [`MovementController`].
+    locked_by_animation: bool,
 }
 
 impl PlayerAnimation {
-    /// The number of idle frames.
-    const IDLE_FRAMES: usize = 8;
-    /// The duration of each idle frame.
-    const IDLE_INTERVAL: Duration = Duration::from_millis(100);
-    /// The number of walking frames.
-    const RUN_FRAMES: usize = 8;
-    /// The duration of each walking frame.
-    const RUN_INTERVAL: Duration = Duration::from_millis(50);
-
-    fn idling_north() -> Self {
-        Self {
-            timer: Timer::new(Self::IDLE_INTERVAL, TimerMode::Repeating),
-            frame: 0,
-            state: PlayerAnimationState::Idling,
-            direction: PlayerDirection::North,
-        }
-    }
-    fn idling_south() -> Self {
-        Self {
-            timer: Timer::new(Self::IDLE_INTERVAL, TimerMode::Repeating),
-            frame: 0,
-            state: PlayerAnimationState::Idling,
-            direction: PlayerDirection::South,
-        }
-    }
-    fn idling_east() -> Self {
-        Self {
-            timer: Timer::new(Self::IDLE_INTERVAL, TimerMode::Repeating),
-            frame: 0,
-            state: PlayerAnimationState::Idling,
-            direction: PlayerDirection::East,
-        }
-    }
-    fn idling_west() -> Self {
-        Self {
-            timer: Timer::new(Self::IDLE_INTERVAL, TimerMode::Repeating),
-            frame: 0,
-            state: PlayerAnimationState::Idling,
-            direction: PlayerDirection::West,
-        }
-    }
-
-    fn running_north() -> Self {
-        Self {
-            timer: Timer::new(Self::RUN_INTERVAL, TimerMode::Repeating),
-            frame: 0,
-            state: PlayerAnimationState::Running,
-            direction: PlayerDirection::North,
-        }
-    }
-    fn running_south() -> Self {
-        Self {
-            timer: Timer::new(Self::RUN_INTERVAL, TimerMode::Repeating),
-            frame: 0,
-            state: PlayerAnimationState::Running,
-            direction: PlayerDirection::South,
-        }
-    }
-    fn running_east() -> Self {
-        Self {
-            timer: Timer::new(Self::RUN_INTERVAL, TimerMode::Repeating),
-            frame: 0,
-            state: PlayerAnimationState::Running,
-            direction: PlayerDirection::East,
-        }
-    }
-    fn running_west() -> Self {
-        Self {
-            timer: Timer::new(Self::RUN_INTERVAL, TimerMode::Repeating),
-            frame: 0,
-            state: PlayerAnimationState::Running,
-            direction: PlayerDirection::West,
-        }
-    }
-
     pub fn new() -> Self {
-        Self::idling_south()
-    }
-
-    /// Update animation timers.
-    pub fn update_timer(&mut self, delta: Duration) {
-        self.timer.tick(delta);
-        if !self.timer.is_finished() {
-            return;
-        }
-        self.frame = (self.frame + 1)
-            % match self.state {
-                PlayerAnimationState::Idling => Self::IDLE_FRAMES,
-                PlayerAnimationState::Running => Self::RUN_FRAMES,
-            };
-    }
-
-    /// Update animation state and direction if it changes.
-    pub fn update_state_and_direction(
-        &mut self,
-        state: PlayerAnimationState,
-        direction: PlayerDirection,
-    ) {
-        if self.state != state || self.direction != direction {
-            match (state, direction) {
-                (PlayerAnimationState::Idling, PlayerDirection::North) => {
-                    *self = Self::idling_north()
-                }
-                (PlayerAnimationState::Idling, PlayerDirection::South) => {
-                    *self = Self::idling_south()
-                }
-                (PlayerAnimationState::Idling, PlayerDirection::East) => {
-                    *self = Self::idling_east()
-                }
-                (PlayerAnimationState::Idling, PlayerDirection::West) => {
-                    *self = Self::idling_west()
-                }
-                (PlayerAnimationState::Running, PlayerDirection::North) => {
-                    *self = Self::running_north()
-                }
-                (PlayerAnimationState::Running, PlayerDirection::South) => {
-                    *self = Self::running_south()
-                }
-                (PlayerAnimationState::Running, PlayerDirection::East) => {
-                    *self = Self::running_east()
-                }
-                (PlayerAnimationState::Running, PlayerDirection::West) => {
-                    *self = Self::running_west()
-                }
-            }
-        }
-    }
-
-    /// Whether animation changed this tick.
-    pub fn changed(&self) -> bool {
-        self.timer.is_finished()
-    }
-
-    /// Return sprite index in the atlas.
-    pub fn get_atlas_index(&self) -> usize {
-        match self.state {
-            PlayerAnimationState::Idling => self.frame,
-            PlayerAnimationState::Running => self.frame,
+        Self {
+            base: BaseAnimation::Idling,
+            direction: CompassDirection::South,
+            one_shot: None,
+            locked_by_animation: false,
         }
     }
 }
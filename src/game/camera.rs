@@ -0,0 +1,288 @@
+//! Make the main 2D camera follow a [`CameraTarget`].
+//!
+//! The camera doesn't stick to its target. Instead, the target can move around
+//! a dead zone in the middle of the screen, the camera looks ahead of where it
+//! is going, catches up smoothly, and never shows anything past the edges of
+//! the current map.
+
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        movement::MovementController,
+        pixel_perfect::{PixelPerfect, UpscaleCamera},
+        tiled_map::MapBounds,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<CameraFollow>();
+    app.init_resource::<CameraSmoothing>();
+    app.init_resource::<CameraZoom>();
+
+    app.add_systems(
+        Update,
+        zoom_camera
+            .in_set(AppSystems::RecordInput)
+            .in_set(PausableSystems),
+    );
+    app.add_systems(
+        Update,
+        apply_camera_zoom
+            .run_if(resource_changed::<CameraZoom>.or(resource_changed::<PixelPerfect>))
+            .in_set(AppSystems::Update),
+    );
+    // Run after movement, so that the camera doesn't lag a frame behind its target.
+    app.add_systems(
+        PostUpdate,
+        follow_camera_target.before(TransformSystems::Propagate),
+    );
+}
+
+/// The entity the main camera follows.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct CameraTarget;
+
+/// How many screen pixels a world unit covers. Above `1.0` zooms in.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct CameraZoom(pub f32);
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+const MIN_ZOOM: f32 = 1.0 / 3.0;
+const MAX_ZOOM: f32 = 4.0;
+
+/// How the camera catches up with its target.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub enum CameraSmoothing {
+    /// Stick to the target.
+    None,
+    /// Close a share of the distance every frame. Higher rates catch up faster.
+    Lerp { decay_rate: f32 },
+    /// Pull towards the target like a damped spring, which can overshoot a
+    /// little when the target stops.
+    Spring { stiffness: f32, damping: f32 },
+}
+
+impl Default for CameraSmoothing {
+    fn default() -> Self {
+        Self::Lerp { decay_rate: 8.0 }
+    }
+}
+
+impl CameraSmoothing {
+    /// The choices offered in the settings menu.
+    const PRESETS: [Self; 3] = [
+        Self::None,
+        Self::Lerp { decay_rate: 8.0 },
+        Self::Spring {
+            stiffness: 60.0,
+            damping: 11.0,
+        },
+    ];
+
+    /// The next preset in the settings menu.
+    pub fn next(&self) -> Self {
+        self.step(1)
+    }
+
+    /// The previous preset in the settings menu.
+    pub fn previous(&self) -> Self {
+        self.step(Self::PRESETS.len() - 1)
+    }
+
+    fn step(&self, offset: usize) -> Self {
+        let index = Self::PRESETS
+            .iter()
+            .position(|preset| preset == self)
+            .unwrap_or_default();
+        Self::PRESETS[(index + offset) % Self::PRESETS.len()]
+    }
+
+    /// A human-readable name for the settings menu.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::None => "Off",
+            Self::Lerp { .. } => "Smooth",
+            Self::Spring { .. } => "Springy",
+        }
+    }
+}
+
+/// How the main camera follows its [`CameraTarget`]. How it catches up is set
+/// separately by the [`CameraSmoothing`] resource, which players can change.
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct CameraFollow {
+    /// Half the size of the area around the center of the screen in which the
+    /// target can move without the camera following it.
+    pub dead_zone: Vec2,
+    /// How far ahead of the target the camera looks when it moves at full speed.
+    pub look_ahead: f32,
+    /// How quickly the look-ahead follows changes of direction.
+    pub look_ahead_rate: f32,
+    /// Whether to keep the edges of the current map's [`MapBounds`] from
+    /// scrolling into view.
+    pub clamp_to_map: bool,
+    /// The point the camera is moving towards, which lags behind the target
+    /// because of the dead zone.
+    focus: Vec2,
+    /// The current look-ahead offset.
+    look_ahead_offset: Vec2,
+    /// The camera's velocity, for [`CameraSmoothing::Spring`].
+    velocity: Vec2,
+}
+
+impl Default for CameraFollow {
+    fn default() -> Self {
+        Self {
+            dead_zone: Vec2::new(32.0, 24.0),
+            look_ahead: 48.0,
+            look_ahead_rate: 3.0,
+            clamp_to_map: true,
+            focus: Vec2::ZERO,
+            look_ahead_offset: Vec2::ZERO,
+            velocity: Vec2::ZERO,
+        }
+    }
+}
+
+/// Zoom in and out with the mouse wheel. Pixel-perfect rendering only zooms in
+/// whole steps, as it can only scale the world up by whole numbers.
+fn zoom_camera(
+    pixel_perfect: Res<PixelPerfect>,
+    mut zoom: ResMut<CameraZoom>,
+    mut mouse_wheel: MessageReader<MouseWheel>,
+) {
+    // Accumulate scroll input this frame
+    let mut scroll = 0.0f32;
+    for ev in mouse_wheel.read() {
+        let step = match ev.unit {
+            MouseScrollUnit::Line => 0.1,
+            MouseScrollUnit::Pixel => 0.001,
+        };
+        scroll += ev.y as f32 * step;
+    }
+    if scroll == 0.0 {
+        return;
+    }
+
+    // Scroll up -> zoom in.
+    zoom.0 = if pixel_perfect.0 {
+        (zoom.0.round() + scroll.signum()).clamp(1.0, MAX_ZOOM)
+    } else {
+        (zoom.0 / (1.0 - scroll).max(0.0001)).clamp(MIN_ZOOM, MAX_ZOOM)
+    };
+}
+
+/// Scale the world camera to the zoom level. Pixel-perfect rendering scales up
+/// its canvas instead (see the `pixel_perfect` module).
+fn apply_camera_zoom(
+    zoom: Res<CameraZoom>,
+    pixel_perfect: Res<PixelPerfect>,
+    mut camera_query: Query<&mut Transform, (With<Camera2d>, Without<UpscaleCamera>)>,
+) {
+    let scale = if pixel_perfect.0 { 1.0 } else { 1.0 / zoom.0 };
+    for mut transform in &mut camera_query {
+        // Keep Z scale unchanged.
+        transform.scale.x = scale;
+        transform.scale.y = scale;
+    }
+}
+
+pub(super) fn follow_camera_target(
+    time: Res<Time>,
+    mut follow: ResMut<CameraFollow>,
+    smoothing: Res<CameraSmoothing>,
+    map_bounds: Res<MapBounds>,
+    target: Single<(Ref<CameraTarget>, &Transform, Option<&MovementController>)>,
+    mut camera_query: Query<
+        (&Camera, &mut Transform),
+        (With<Camera2d>, Without<CameraTarget>, Without<UpscaleCamera>),
+    >,
+) {
+    let (marker, target_transform, controller) = target.into_inner();
+    let target = target_transform.translation.xy();
+    let dt = time.delta_secs();
+
+    let intent = controller.map_or(Vec2::ZERO, |controller| controller.intent);
+    let look_ahead = intent.clamp_length_max(1.0) * follow.look_ahead;
+    let rate = follow.look_ahead_rate;
+    follow.look_ahead_offset.smooth_nudge(&look_ahead, rate, dt);
+
+    // Only move the focus once the target leaves the dead zone around it.
+    let offset = target - follow.focus;
+    let dead_zone = follow.dead_zone;
+    follow.focus += offset - offset.clamp(-dead_zone, dead_zone);
+
+    for (camera, mut transform) in &mut camera_query {
+        let desired = follow.focus + follow.look_ahead_offset;
+        let current = transform.translation.xy();
+
+        // Snap to a new target, e.g. when a level is spawned.
+        let mut position = if marker.is_added() {
+            follow.focus = target;
+            follow.look_ahead_offset = Vec2::ZERO;
+            follow.velocity = Vec2::ZERO;
+            target
+        } else {
+            match *smoothing {
+                CameraSmoothing::None => desired,
+                CameraSmoothing::Lerp { decay_rate } => {
+                    let mut position = current;
+                    position.smooth_nudge(&desired, decay_rate, dt);
+                    position
+                }
+                CameraSmoothing::Spring { stiffness, damping } => {
+                    let acceleration =
+                        (desired - current) * stiffness - follow.velocity * damping;
+                    follow.velocity += acceleration * dt;
+                    current + follow.velocity * dt
+                }
+            }
+        };
+
+        if follow.clamp_to_map
+            && let Some(bounds) = map_bounds.0
+            && let Some(viewport) = camera.logical_viewport_size()
+        {
+            let half_view = viewport * 0.5 * transform.scale.xy();
+            position = clamp_view(position, half_view, bounds);
+        }
+
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+/// Move a view centered on `position` inside `bounds`, or center it on them if
+/// it is larger.
+fn clamp_view(position: Vec2, half_view: Vec2, bounds: Rect) -> Vec2 {
+    let min = bounds.min + half_view;
+    let max = bounds.max - half_view;
+    let center = bounds.center();
+    Vec2::new(
+        if min.x <= max.x {
+            position.x.clamp(min.x, max.x)
+        } else {
+            center.x
+        },
+        if min.y <= max.y {
+            position.y.clamp(min.y, max.y)
+        } else {
+            center.y
+        },
+    )
+}
@@ -0,0 +1,148 @@
+//! Camera shake for hits, explosions and other impacts.
+//!
+//! Gameplay code adds trauma to the [`CameraShake`] resource, and the camera
+//! shakes by the square of the trauma, which decays over time. The shake moves
+//! and rotates the camera along smooth noise, on top of where
+//! [`follow_camera_target`] put it, and is turned off by [`ReducedMotion`].
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    camera::follow_camera_target, pixel_perfect::UpscaleCamera, sprite_animation::AnimationEvent,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<CameraShake>();
+    app.init_resource::<ReducedMotion>();
+
+    app.add_observer(shake_on_impact);
+
+    app.add_systems(
+        PostUpdate,
+        (
+            remove_camera_shake.before(follow_camera_target),
+            apply_camera_shake
+                .after(follow_camera_target)
+                .before(TransformSystems::Propagate),
+        ),
+    );
+}
+
+/// How much trauma an `impact` event in an animation clip adds.
+const IMPACT_TRAUMA: f32 = 0.3;
+
+/// An accessibility setting that turns off camera shake.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct ReducedMotion(pub bool);
+
+/// Shakes the main 2D camera.
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct CameraShake {
+    /// How far the camera moves at full trauma, in world units.
+    pub max_offset: Vec2,
+    /// How far the camera rotates at full trauma, in radians.
+    pub max_rotation: f32,
+    /// How much trauma is lost per second.
+    pub decay: f32,
+    /// How quickly the camera shakes back and forth.
+    pub frequency: f32,
+    /// From `0.0` (still) to `1.0` (shaking as hard as possible).
+    trauma: f32,
+    /// The offset and rotation applied to the camera this frame, to be undone
+    /// before the camera follows its target again.
+    applied_offset: Vec2,
+    applied_rotation: f32,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self {
+            max_offset: Vec2::splat(12.0),
+            max_rotation: 0.05,
+            decay: 1.5,
+            frequency: 15.0,
+            trauma: 0.0,
+            applied_offset: Vec2::ZERO,
+            applied_rotation: 0.0,
+        }
+    }
+}
+
+impl CameraShake {
+    /// Add trauma, from `0.0` to `1.0`. Trauma adds up, so repeated impacts
+    /// shake the camera harder, up to the maximum.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+}
+
+fn shake_on_impact(animation_event: On<AnimationEvent>, mut shake: ResMut<CameraShake>) {
+    if animation_event.name == "impact" {
+        shake.add_trauma(IMPACT_TRAUMA);
+    }
+}
+
+/// Undo last frame's shake, so that the camera follows its target from where it
+/// would be without it.
+pub(super) fn remove_camera_shake(
+    mut shake: ResMut<CameraShake>,
+    mut camera_query: Query<&mut Transform, (With<Camera2d>, Without<UpscaleCamera>)>,
+) {
+    if shake.applied_offset == Vec2::ZERO && shake.applied_rotation == 0.0 {
+        return;
+    }
+    for mut transform in &mut camera_query {
+        transform.translation -= shake.applied_offset.extend(0.0);
+        transform.rotate_z(-shake.applied_rotation);
+    }
+    shake.applied_offset = Vec2::ZERO;
+    shake.applied_rotation = 0.0;
+}
+
+pub(super) fn apply_camera_shake(
+    time: Res<Time>,
+    reduced_motion: Res<ReducedMotion>,
+    mut shake: ResMut<CameraShake>,
+    mut camera_query: Query<&mut Transform, (With<Camera2d>, Without<UpscaleCamera>)>,
+) {
+    let decay = shake.decay * time.delta_secs();
+    shake.trauma = (shake.trauma - decay).max(0.0);
+    if shake.trauma == 0.0 || reduced_motion.0 {
+        return;
+    }
+
+    let amount = shake.trauma * shake.trauma;
+    let t = time.elapsed_secs() * shake.frequency;
+    shake.applied_offset = shake.max_offset * amount * Vec2::new(noise(0, t), noise(1, t));
+    shake.applied_rotation = shake.max_rotation * amount * noise(2, t);
+    for mut transform in &mut camera_query {
+        transform.translation += shake.applied_offset.extend(0.0);
+        transform.rotate_z(shake.applied_rotation);
+    }
+}
+
+/// Smooth noise from `-1.0` to `1.0`, with a different curve for each `seed`.
+fn noise(seed: u32, t: f32) -> f32 {
+    let cell = t.floor();
+    let fraction = t - cell;
+    let a = lattice_value(seed, cell as i32);
+    let b = lattice_value(seed, cell as i32 + 1);
+    // Smoothstep between random values at whole numbers.
+    a + (b - a) * fraction * fraction * (3.0 - 2.0 * fraction)
+}
+
+/// A pseudorandom value from `-1.0` to `1.0` for a whole number.
+fn lattice_value(seed: u32, x: i32) -> f32 {
+    let mut hash = (x as u32)
+        .wrapping_mul(0x9E37_79B9)
+        .wrapping_add(seed.wrapping_mul(0x85EB_CA6B));
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x7FEB_352D);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x846C_A68B);
+    hash ^= hash >> 16;
+    hash as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
@@ -0,0 +1,325 @@
+//! Health, damage and attacks.
+//!
+//! Attacks land on the `impact` event of the attacker's animation clip (see the
+//! `sprite_animation` module), and hit every entity with [`Health`] in reach in
+//! front of the attacker. Damage is scaled by the attacker's and the target's
+//! [`Stats`], and [`Defeated`] is triggered when a target's health runs out.
+//!
+//! Enemies attack the player whenever it stays close to them for long enough.
+
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        experience::ExperienceReward,
+        facing::Facing,
+        flags::GameFlags,
+        footsteps::Footsteps,
+        inventory::{ItemStack, Pickup, pickup},
+        player::Player,
+        sprite_animation::AnimationEvent,
+        stats::{StatValues, Stats},
+        tiled_map::MapObject,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(attack_on_impact);
+    app.add_observer(take_damage);
+    app.add_observer(drop_enemy_items);
+    app.add_observer(set_defeated_flag);
+    app.add_observer(despawn_defeated_enemies);
+
+    app.add_systems(
+        Update,
+        (
+            tick_enemy_attacks.in_set(AppSystems::TickTimers),
+            attack_player.in_set(AppSystems::Update),
+        )
+            .in_set(PausableSystems),
+    );
+}
+
+/// How far in front of an attacker its attacks reach.
+const ATTACK_REACH: f32 = 24.0;
+
+/// How far from the point of impact attacks hit.
+const ATTACK_RADIUS: f32 = 20.0;
+
+/// How much damage an entity can take before it is [`Defeated`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    /// Change the maximum health, keeping the same share of it.
+    pub fn set_max(&mut self, max: f32) {
+        let fraction = if self.max > 0.0 {
+            self.current / self.max
+        } else {
+            1.0
+        };
+        self.max = max;
+        self.current = max * fraction;
+    }
+}
+
+/// How close the player has to be for an enemy to attack it.
+const ENEMY_ATTACK_RANGE: f32 = 16.0;
+
+/// How long the player has to stay in range of an enemy to be attacked, and
+/// how long the enemy waits between attacks.
+const ENEMY_ATTACK_INTERVAL: Duration = Duration::from_millis(1200);
+
+/// How far apart the items an enemy drops are spread.
+const DROP_SPREAD: f32 = 6.0;
+
+const ENEMY_SIZE: f32 = 14.0;
+const ENEMY_COLOR: Color = Color::srgb(0.8, 0.25, 0.25);
+
+/// A character that the player fights. Enemies are despawned when they are
+/// [`Defeated`].
+#[derive(Component, Debug, Clone, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+pub struct Enemy {
+    /// What kind of enemy this is, e.g. `Slime`.
+    pub kind: String,
+}
+
+/// The time until an [`Enemy`] attacks the player, which only runs while the
+/// player is in range.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct EnemyAttack(Timer);
+
+impl Default for EnemyAttack {
+    fn default() -> Self {
+        Self(Timer::new(ENEMY_ATTACK_INTERVAL, TimerMode::Repeating))
+    }
+}
+
+/// The items an [`Enemy`] drops where it stood when it is [`Defeated`].
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct EnemyDrops(pub Vec<ItemStack>);
+
+/// The flag set when an [`Enemy`] placed in a map is [`Defeated`], so that it
+/// stays defeated.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct DefeatedFlag(pub String);
+
+/// An enemy of a `kind` at `position` with `max_health`, granting `experience`
+/// when defeated.
+pub fn enemy(kind: &str, max_health: f32, experience: u32, position: Vec2) -> impl Bundle {
+    (
+        Name::new(format!("Enemy {kind}")),
+        Enemy {
+            kind: kind.to_string(),
+        },
+        Stats::new(StatValues {
+            strength: 5.0,
+            defense: 0.0,
+            speed: 0.0,
+            max_health,
+        }),
+        Health::new(max_health),
+        EnemyAttack::default(),
+        ExperienceReward(experience),
+        Sprite::from_color(ENEMY_COLOR, Vec2::splat(ENEMY_SIZE)),
+        Footsteps::default(),
+        // Level with characters.
+        Transform::from_translation(position.extend(3.0)),
+    )
+}
+
+/// Damage an entity with [`Health`].
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct Damage {
+    pub entity: Entity,
+    /// The damage before the target's defense.
+    pub amount: f32,
+    /// Who dealt the damage.
+    pub source: Option<Entity>,
+}
+
+/// Triggered on an entity whose [`Health`] runs out.
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct Defeated {
+    pub entity: Entity,
+    /// Who dealt the final blow.
+    pub by: Option<Entity>,
+}
+
+fn attack_on_impact(
+    animation_event: On<AnimationEvent>,
+    mut commands: Commands,
+    attacker_query: Query<(&Stats, &Facing, &GlobalTransform)>,
+    target_query: Query<(Entity, &GlobalTransform), With<Health>>,
+) {
+    if animation_event.name != "impact" {
+        return;
+    }
+    let attacker = animation_event.entity;
+    let Ok((stats, facing, transform)) = attacker_query.get(attacker) else {
+        return;
+    };
+    let impact = transform.translation().xy() + facing.direction.vector() * ATTACK_REACH;
+    for (target, target_transform) in &target_query {
+        let in_reach = target_transform.translation().xy().distance(impact) <= ATTACK_RADIUS;
+        if target != attacker && in_reach {
+            commands.trigger(Damage {
+                entity: target,
+                amount: stats.attack_damage(),
+                source: Some(attacker),
+            });
+        }
+    }
+}
+
+fn take_damage(
+    damage: On<Damage>,
+    mut commands: Commands,
+    mut target_query: Query<(&mut Health, Option<&Stats>)>,
+) {
+    let Ok((mut health, stats)) = target_query.get_mut(damage.entity) else {
+        return;
+    };
+    if health.current <= 0.0 {
+        return;
+    }
+    let amount = stats.map_or(damage.amount, |stats| stats.damage_taken(damage.amount));
+    health.current = (health.current - amount).max(0.0);
+    if health.current == 0.0 {
+        commands.trigger(Defeated {
+            entity: damage.entity,
+            by: damage.source,
+        });
+    }
+}
+
+/// Run the attack timers of enemies with the player in range, and start them
+/// over for the others.
+fn tick_enemy_attacks(
+    time: Res<Time>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut enemy_query: Query<(&GlobalTransform, &mut EnemyAttack)>,
+) {
+    for (transform, mut attack) in &mut enemy_query {
+        let position = transform.translation().xy();
+        let in_range = player_query.iter().any(|player_transform| {
+            player_transform.translation().xy().distance(position) <= ENEMY_ATTACK_RANGE
+        });
+        if in_range {
+            attack.0.tick(time.delta());
+        } else if attack.0.elapsed() > Duration::ZERO {
+            attack.0.reset();
+        }
+    }
+}
+
+fn attack_player(
+    mut commands: Commands,
+    player_query: Query<(Entity, &GlobalTransform), With<Player>>,
+    enemy_query: Query<(Entity, &GlobalTransform, &Stats, &EnemyAttack)>,
+) {
+    for (enemy, transform, stats, attack) in &enemy_query {
+        if !attack.0.just_finished() {
+            continue;
+        }
+        let position = transform.translation().xy();
+        for (player, player_transform) in &player_query {
+            if player_transform.translation().xy().distance(position) <= ENEMY_ATTACK_RANGE {
+                commands.trigger(Damage {
+                    entity: player,
+                    amount: stats.attack_damage(),
+                    source: Some(enemy),
+                });
+            }
+        }
+    }
+}
+
+/// Drop the items of a defeated enemy on the map it was on.
+fn drop_enemy_items(
+    defeated: On<Defeated>,
+    mut commands: Commands,
+    enemy_query: Query<(&EnemyDrops, &Transform, &ChildOf), With<Enemy>>,
+) {
+    let Ok((drops, transform, child_of)) = enemy_query.get(defeated.entity) else {
+        return;
+    };
+    let count = drops.0.len();
+    for (index, stack) in drops.0.iter().enumerate() {
+        let offset = if count > 1 {
+            Vec2::from_angle(index as f32 * TAU / count as f32) * DROP_SPREAD
+        } else {
+            Vec2::ZERO
+        };
+        commands.spawn((
+            pickup(Pickup::new(stack.clone()), transform.translation.xy() + offset),
+            MapObject,
+            ChildOf(child_of.parent()),
+        ));
+    }
+}
+
+fn set_defeated_flag(
+    defeated: On<Defeated>,
+    flag_query: Query<&DefeatedFlag>,
+    mut flags: ResMut<GameFlags>,
+) {
+    if let Ok(flag) = flag_query.get(defeated.entity) {
+        flags.set(flag.0.clone());
+    }
+}
+
+fn despawn_defeated_enemies(
+    defeated: On<Defeated>,
+    mut commands: Commands,
+    enemy_query: Query<(), With<Enemy>>,
+) {
+    if enemy_query.contains(defeated.entity) {
+        commands.entity(defeated.entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raising_max_health_keeps_the_same_share_of_health() {
+        let mut health = Health::new(100.0);
+        health.current = 50.0;
+        health.set_max(120.0);
+        assert_eq!(health.max, 120.0);
+        assert_eq!(health.current, 60.0);
+    }
+
+    #[test]
+    fn full_health_stays_full_when_max_health_changes() {
+        let mut health = Health::new(100.0);
+        health.set_max(80.0);
+        assert_eq!(health.current, 80.0);
+        health.set_max(150.0);
+        assert_eq!(health.current, 150.0);
+    }
+
+    #[test]
+    fn setting_max_health_from_zero_fills_it() {
+        let mut health = Health::new(0.0);
+        health.set_max(30.0);
+        assert_eq!(health.current, 30.0);
+    }
+}
@@ -0,0 +1,469 @@
+//! Conversations with NPCs, driven by `.dialogue.ron` script assets.
+//!
+//! A script is a set of nodes by id. Each node has lines of text, shown one at
+//! a time, and then either offers choices or moves on to its `next` node. A
+//! conversation ends when it moves on to no node at all. Entering a node first
+//! follows the first of its `branches` whose condition holds, and then applies
+//! the node's effects:
+//!
+//! ```ron
+//! (
+//!     start: "greeting",
+//!     nodes: {
+//!         "greeting": (
+//!             branches: [(condition: Flag("got_potion"), next: "again")],
+//!             lines: ["Hello, traveler.", "These roads are dangerous."],
+//!             choices: [
+//!                 (text: "Can you help me?", next: Some("gift")),
+//!                 (text: "Goodbye."),
+//!             ],
+//!         ),
+//!         "gift": (
+//!             lines: ["Take this, and be careful."],
+//!             effects: [GiveItem("haste_potion", 1), SetFlag("got_potion")],
+//!         ),
+//!         "again": (lines: ["Safe travels."]),
+//!     },
+//! )
+//! ```
+//!
+//! Interacting with an [`Npc`] starts its script with [`StartDialogue`], which
+//! opens [`Menu::Dialogue`]. The menu shows the [`ActiveDialogue`] and moves it
+//! along with [`GoToDialogueNode`].
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    Pause,
+    game::{
+        flags::GameFlags,
+        footsteps::Footsteps,
+        interaction::{Interact, Interactable},
+        inventory::{GiveItem, ItemStack},
+        player::Player,
+        quests::StartQuest,
+    },
+    menus::Menu,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<DialogueScript>();
+    app.register_asset_loader(DialogueScriptLoader);
+
+    app.add_observer(talk_to);
+    app.add_observer(start_dialogue);
+    app.add_observer(go_to_dialogue_node);
+}
+
+/// How close the player has to be to an [`Npc`] to talk to it.
+const TALK_RADIUS: f32 = 24.0;
+
+const NPC_SIZE: f32 = 14.0;
+const NPC_COLOR: Color = Color::srgb(0.35, 0.65, 0.9);
+
+/// Something that has to hold for a dialogue branch to be taken, or a choice
+/// to be offered.
+#[derive(Debug, Clone, PartialEq, Eq, Reflect, Deserialize)]
+pub enum DialogueCondition {
+    /// The flag is set.
+    Flag(String),
+    /// The flag isn't set.
+    NotFlag(String),
+    /// All of the conditions hold.
+    All(Vec<DialogueCondition>),
+}
+
+impl DialogueCondition {
+    pub fn holds(&self, flags: &GameFlags) -> bool {
+        match self {
+            Self::Flag(flag) => flags.is_set(flag),
+            Self::NotFlag(flag) => !flags.is_set(flag),
+            Self::All(conditions) => conditions.iter().all(|condition| condition.holds(flags)),
+        }
+    }
+}
+
+/// Something that happens when a dialogue node is entered.
+#[derive(Debug, Clone, PartialEq, Eq, Reflect, Deserialize)]
+pub enum DialogueEffect {
+    SetFlag(String),
+    ClearFlag(String),
+    /// Give the player a number of an item. Items that don't fit in the
+    /// inventory are dropped at the player's feet.
+    GiveItem(String, u32),
+    /// Start the quest with this id.
+    StartQuest(String),
+}
+
+/// Jump straight to another node if `condition` holds.
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct DialogueBranch {
+    pub condition: DialogueCondition,
+    pub next: String,
+}
+
+/// An answer the player can pick at the end of a node.
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct DialogueChoice {
+    pub text: String,
+    /// The choice is only offered while this holds.
+    #[serde(default)]
+    pub condition: Option<DialogueCondition>,
+    /// The node to go to, or `None` to end the conversation.
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+/// A single node of a [`DialogueScript`].
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct DialogueNode {
+    /// Who is talking, if not the NPC.
+    #[serde(default)]
+    pub speaker: Option<String>,
+    #[serde(default)]
+    pub branches: Vec<DialogueBranch>,
+    pub lines: Vec<String>,
+    #[serde(default)]
+    pub effects: Vec<DialogueEffect>,
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+    /// The node to go to when there are no choices to offer, or `None` to end
+    /// the conversation.
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+impl DialogueNode {
+    /// The choices whose conditions hold.
+    pub fn available_choices<'a>(
+        &'a self,
+        flags: &'a GameFlags,
+    ) -> impl Iterator<Item = &'a DialogueChoice> {
+        self.choices.iter().filter(|choice| {
+            choice
+                .condition
+                .as_ref()
+                .is_none_or(|condition| condition.holds(flags))
+        })
+    }
+}
+
+/// A conversation, loaded from a `.dialogue.ron` file.
+#[derive(Asset, Debug, Reflect, Deserialize)]
+pub struct DialogueScript {
+    /// The id of the node the conversation starts at.
+    pub start: String,
+    pub nodes: HashMap<String, DialogueNode>,
+}
+
+impl DialogueScript {
+    /// A script that just shows some lines, e.g. the text on a sign.
+    pub fn message(lines: Vec<String>) -> Self {
+        let node = DialogueNode {
+            speaker: None,
+            branches: Vec::new(),
+            lines,
+            effects: Vec::new(),
+            choices: Vec::new(),
+            next: None,
+        };
+        Self {
+            start: "message".to_string(),
+            nodes: HashMap::from([("message".to_string(), node)]),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DialogueScriptLoaderError {
+    #[error("Could not load dialogue file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse dialogue file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Dialogue refers to unknown node `{0}`")]
+    UnknownNode(String),
+    #[error("Dialogue node `{0}` has no lines")]
+    NoLines(String),
+}
+
+pub struct DialogueScriptLoader;
+
+impl AssetLoader for DialogueScriptLoader {
+    type Asset = DialogueScript;
+    type Settings = ();
+    type Error = DialogueScriptLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        parse_dialogue_script(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dialogue.ron"]
+    }
+}
+
+/// Parse a dialogue script, checking that every node has lines and every node
+/// it refers to exists.
+fn parse_dialogue_script(bytes: &[u8]) -> Result<DialogueScript, DialogueScriptLoaderError> {
+    let script: DialogueScript = ron::de::from_bytes(bytes)?;
+
+    let check_node = |id: &String| {
+        if script.nodes.contains_key(id) {
+            Ok(())
+        } else {
+            Err(DialogueScriptLoaderError::UnknownNode(id.clone()))
+        }
+    };
+    check_node(&script.start)?;
+    for (id, node) in &script.nodes {
+        if node.lines.is_empty() {
+            return Err(DialogueScriptLoaderError::NoLines(id.clone()));
+        }
+        let choices = node.choices.iter().filter_map(|choice| choice.next.as_ref());
+        let branches = node.branches.iter().map(|branch| &branch.next);
+        for next in node.next.iter().chain(choices).chain(branches) {
+            check_node(next)?;
+        }
+    }
+    Ok(script)
+}
+
+/// A character the player can talk to.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Npc {
+    pub name: String,
+    pub dialogue: Handle<DialogueScript>,
+}
+
+/// An NPC at `position`.
+pub fn npc(npc: Npc, position: Vec2) -> impl Bundle {
+    (
+        Name::new(format!("NPC {}", npc.name)),
+        npc,
+        Interactable::new(TALK_RADIUS, "Talk"),
+        Sprite::from_color(NPC_COLOR, Vec2::splat(NPC_SIZE)),
+        Footsteps::default(),
+        // Level with characters.
+        Transform::from_translation(position.extend(3.0)),
+    )
+}
+
+/// Start a conversation, opening [`Menu::Dialogue`].
+#[derive(Event, Debug, Clone)]
+pub struct StartDialogue {
+    pub script: Handle<DialogueScript>,
+    /// The name shown for nodes without a speaker of their own.
+    pub speaker: String,
+}
+
+/// Move the [`ActiveDialogue`] on to another node, or end it with `None`.
+#[derive(Event, Debug, Clone)]
+pub struct GoToDialogueNode {
+    pub node: Option<String>,
+}
+
+/// The conversation that is going on, while [`Menu::Dialogue`] is open.
+#[derive(Resource, Debug)]
+pub struct ActiveDialogue {
+    pub script: Handle<DialogueScript>,
+    /// The name shown for nodes without a speaker of their own.
+    pub speaker: String,
+    /// The id of the current node.
+    pub node: String,
+    /// The index of the current line of the node.
+    pub line: usize,
+    /// How many characters of the current line have been revealed so far.
+    pub revealed: f32,
+}
+
+impl ActiveDialogue {
+    pub fn current_node<'a>(
+        &self,
+        scripts: &'a Assets<DialogueScript>,
+    ) -> Option<&'a DialogueNode> {
+        scripts.get(&self.script)?.nodes.get(&self.node)
+    }
+
+    pub fn current_line<'a>(&self, scripts: &'a Assets<DialogueScript>) -> Option<&'a str> {
+        self.current_node(scripts)?
+            .lines
+            .get(self.line)
+            .map(String::as_str)
+    }
+}
+
+fn talk_to(interact: On<Interact>, mut commands: Commands, npc_query: Query<&Npc>) {
+    let Ok(npc) = npc_query.get(interact.entity) else {
+        return;
+    };
+    commands.trigger(StartDialogue {
+        script: npc.dialogue.clone(),
+        speaker: npc.name.clone(),
+    });
+}
+
+fn start_dialogue(
+    start: On<StartDialogue>,
+    mut commands: Commands,
+    scripts: Res<Assets<DialogueScript>>,
+    mut next_pause: ResMut<NextState<Pause>>,
+    mut next_menu: ResMut<NextState<Menu>>,
+) {
+    let Some(script) = scripts.get(&start.script) else {
+        return;
+    };
+    commands.insert_resource(ActiveDialogue {
+        script: start.script.clone(),
+        speaker: start.speaker.clone(),
+        node: script.start.clone(),
+        line: 0,
+        revealed: 0.0,
+    });
+    commands.trigger(GoToDialogueNode {
+        node: Some(script.start.clone()),
+    });
+    next_pause.set(Pause(true));
+    next_menu.set(Menu::Dialogue);
+}
+
+fn go_to_dialogue_node(
+    go_to: On<GoToDialogueNode>,
+    mut commands: Commands,
+    scripts: Res<Assets<DialogueScript>>,
+    dialogue: Option<ResMut<ActiveDialogue>>,
+    mut flags: ResMut<GameFlags>,
+    mut next_menu: ResMut<NextState<Menu>>,
+    player: Single<Entity, With<Player>>,
+) {
+    let Some(mut dialogue) = dialogue else {
+        return;
+    };
+    let Some(script) = scripts.get(&dialogue.script) else {
+        next_menu.set(Menu::None);
+        return;
+    };
+
+    // Follow branches, giving up if they go around in circles.
+    let mut id = go_to.node.clone();
+    let mut jumps = 0;
+    while let Some(node) = id.as_ref().and_then(|id| script.nodes.get(id))
+        && let Some(branch) = node.branches.iter().find(|branch| branch.condition.holds(&flags))
+    {
+        jumps += 1;
+        if jumps > script.nodes.len() {
+            warn!("Dialogue branches never settle on a node, ending the conversation");
+            id = None;
+            break;
+        }
+        id = Some(branch.next.clone());
+    }
+    let Some((id, node)) = id.and_then(|id| script.nodes.get_key_value(&id)) else {
+        next_menu.set(Menu::None);
+        return;
+    };
+
+    for effect in &node.effects {
+        match effect {
+            DialogueEffect::SetFlag(flag) => flags.set(flag.clone()),
+            DialogueEffect::ClearFlag(flag) => flags.clear(flag),
+            DialogueEffect::GiveItem(item, count) => commands.trigger(GiveItem {
+                entity: *player,
+                stack: ItemStack {
+                    item: item.clone(),
+                    count: *count,
+                },
+            }),
+            DialogueEffect::StartQuest(quest) => commands.trigger(StartQuest {
+                quest: quest.clone(),
+            }),
+        }
+    }
+
+    dialogue.node = id.clone();
+    dialogue.line = 0;
+    dialogue.revealed = 0.0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Result<DialogueScript, DialogueScriptLoaderError> {
+        parse_dialogue_script(contents.as_bytes())
+    }
+
+    #[test]
+    fn a_script_whose_nodes_all_exist_is_loaded() {
+        let script = parse(
+            r#"(
+                start: "greeting",
+                nodes: {
+                    "greeting": (
+                        branches: [(condition: Flag("met"), next: "again")],
+                        lines: ["Hello."],
+                        choices: [(text: "Bye.", next: Some("again")), (text: "...")],
+                    ),
+                    "again": (lines: ["Hello again."]),
+                },
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(script.start, "greeting");
+        assert_eq!(script.nodes.len(), 2);
+    }
+
+    #[test]
+    fn an_unknown_start_node_is_rejected() {
+        let error = parse(r#"(start: "missing", nodes: {"greeting": (lines: ["Hello."])})"#);
+        assert!(
+            matches!(error, Err(DialogueScriptLoaderError::UnknownNode(id)) if id == "missing")
+        );
+    }
+
+    #[test]
+    fn unknown_next_choice_and_branch_nodes_are_rejected() {
+        for node in [
+            r#"(lines: ["Hello."], next: Some("missing"))"#,
+            r#"(lines: ["Hello."], choices: [(text: "Go.", next: Some("missing"))])"#,
+            r#"(lines: ["Hello."], branches: [(condition: Flag("a"), next: "missing")])"#,
+        ] {
+            let error = parse(&format!(
+                r#"(start: "greeting", nodes: {{"greeting": {node}}})"#
+            ));
+            let rejected = matches!(
+                &error,
+                Err(DialogueScriptLoaderError::UnknownNode(id)) if id == "missing"
+            );
+            assert!(rejected, "{node} was not rejected");
+        }
+    }
+
+    #[test]
+    fn a_node_without_lines_is_rejected() {
+        let error = parse(r#"(start: "greeting", nodes: {"greeting": (lines: [])})"#);
+        assert!(matches!(error, Err(DialogueScriptLoaderError::NoLines(id)) if id == "greeting"));
+    }
+
+    #[test]
+    fn invalid_ron_is_rejected() {
+        assert!(matches!(
+            parse("(start: "),
+            Err(DialogueScriptLoaderError::Ron(_))
+        ));
+    }
+}
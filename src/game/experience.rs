@@ -0,0 +1,187 @@
+//! Experience from defeating enemies, and level-ups.
+//!
+//! Defeating an entity with an [`ExperienceReward`] grants its experience to
+//! whoever dealt the final blow, and [`GainExperience`] grants experience for
+//! anything else, e.g. quests. Each level-up raises the character's base
+//! [`Stats`], restores its [`Health`] and triggers [`LevelUp`], which shows a
+//! notification for the player.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        combat::{Defeated, Health},
+        player::Player,
+        stats::Stats,
+    },
+    screens::Screen,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(grant_experience);
+    app.add_observer(gain_experience);
+    app.add_observer(notify_level_up);
+
+    app.add_systems(
+        Update,
+        (
+            tick_level_up_notifications.in_set(AppSystems::TickTimers),
+            fade_level_up_notifications.in_set(AppSystems::Update),
+        )
+            .in_set(PausableSystems),
+    );
+}
+
+/// How long a level-up notification stays on screen.
+const NOTIFICATION_SECS: f32 = 2.5;
+
+/// A character's level and the experience it has gathered towards the next one.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct Experience {
+    pub level: u32,
+    pub xp: u32,
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Self { level: 1, xp: 0 }
+    }
+}
+
+impl Experience {
+    /// How much experience it takes to go from the current level to the next.
+    pub fn xp_to_next_level(&self) -> u32 {
+        100 * self.level
+    }
+}
+
+/// How much experience defeating this entity grants.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct ExperienceReward(pub u32);
+
+/// Give experience to a character with [`Experience`].
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct GainExperience {
+    pub entity: Entity,
+    pub amount: u32,
+}
+
+/// Triggered on a character when it reaches a new level.
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct LevelUp {
+    pub entity: Entity,
+    pub level: u32,
+}
+
+fn grant_experience(
+    defeated: On<Defeated>,
+    mut commands: Commands,
+    reward_query: Query<&ExperienceReward>,
+) {
+    if let Some(by) = defeated.by
+        && let Ok(reward) = reward_query.get(defeated.entity)
+    {
+        commands.trigger(GainExperience {
+            entity: by,
+            amount: reward.0,
+        });
+    }
+}
+
+fn gain_experience(
+    gain: On<GainExperience>,
+    mut commands: Commands,
+    mut query: Query<(&mut Experience, &mut Stats, Option<&mut Health>)>,
+) {
+    let Ok((mut experience, mut stats, health)) = query.get_mut(gain.entity) else {
+        return;
+    };
+
+    let old_level = experience.level;
+    experience.xp += gain.amount;
+    while experience.xp >= experience.xp_to_next_level() {
+        experience.xp -= experience.xp_to_next_level();
+        experience.level += 1;
+        stats.base.strength += 1.0;
+        stats.base.defense += 1.0;
+        stats.base.max_health += 10.0;
+        commands.trigger(LevelUp {
+            entity: gain.entity,
+            level: experience.level,
+        });
+    }
+    // Max health catches up with the new base stats in `update_stats`, keeping
+    // the share of health, so this restores it in full.
+    if experience.level > old_level
+        && let Some(mut health) = health
+    {
+        health.current = health.max;
+    }
+}
+
+/// Fades out and despawns once its timer finishes.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct LevelUpNotification(Timer);
+
+fn notify_level_up(
+    level_up: On<LevelUp>,
+    mut commands: Commands,
+    player_query: Query<(), With<Player>>,
+    old_notifications: Query<Entity, With<LevelUpNotification>>,
+) {
+    if !player_query.contains(level_up.entity) {
+        return;
+    }
+    for entity in &old_notifications {
+        commands.entity(entity).despawn();
+    }
+    commands.spawn((
+        Name::new("Level Up Notification"),
+        LevelUpNotification(Timer::from_seconds(NOTIFICATION_SECS, TimerMode::Once)),
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            top: percent(20),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        GlobalZIndex(1),
+        DespawnOnExit(Screen::Gameplay),
+        children![widget::header(format!("Level {}!", level_up.level))],
+    ));
+}
+
+fn tick_level_up_notifications(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut LevelUpNotification)>,
+) {
+    for (entity, mut notification) in &mut query {
+        notification.0.tick(time.delta());
+        if notification.0.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Fade notifications out over the last second they are shown.
+fn fade_level_up_notifications(
+    notification_query: Query<(&LevelUpNotification, &Children)>,
+    mut text_query: Query<&mut TextColor>,
+) {
+    for (notification, children) in &notification_query {
+        let alpha = notification.0.remaining_secs().min(1.0);
+        for &child in children {
+            if let Ok(mut color) = text_query.get_mut(child) {
+                color.0.set_alpha(alpha);
+            }
+        }
+    }
+}
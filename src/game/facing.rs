@@ -0,0 +1,201 @@
+//! The direction characters face.
+//!
+//! A character's [`Facing`] follows its aim if it has one, and otherwise its
+//! last movement, so it keeps facing the same way after it stops.
+
+use bevy::prelude::*;
+
+use crate::{AppSystems, PausableSystems, game::movement::MovementController};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        update_facing
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// One of eight screen directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect)]
+pub enum CompassDirection {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    #[default]
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl CompassDirection {
+    /// Every direction, counterclockwise from east.
+    const COUNTERCLOCKWISE: [Self; 8] = [
+        Self::East,
+        Self::NorthEast,
+        Self::North,
+        Self::NorthWest,
+        Self::West,
+        Self::SouthWest,
+        Self::South,
+        Self::SouthEast,
+    ];
+
+    /// The direction closest to `vector`, or `None` if it is zero.
+    pub fn from_vector(vector: Vec2) -> Option<Self> {
+        if vector == Vec2::ZERO {
+            return None;
+        }
+        let eighths = (vector.to_angle() / std::f32::consts::FRAC_PI_4).round() as i32;
+        Some(Self::COUNTERCLOCKWISE[eighths.rem_euclid(8) as usize])
+    }
+
+    /// A unit vector pointing in this direction.
+    pub fn vector(&self) -> Vec2 {
+        let index = Self::COUNTERCLOCKWISE
+            .iter()
+            .position(|direction| direction == self)
+            .unwrap_or_default();
+        Vec2::from_angle(index as f32 * std::f32::consts::FRAC_PI_4)
+    }
+
+    /// The name used for this direction in clip names, e.g. `north_east`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::North => "north",
+            Self::NorthEast => "north_east",
+            Self::East => "east",
+            Self::SouthEast => "south_east",
+            Self::South => "south",
+            Self::SouthWest => "south_west",
+            Self::West => "west",
+            Self::NorthWest => "north_west",
+        }
+    }
+
+    /// This direction mirrored horizontally, e.g. north-west for north-east.
+    pub fn mirrored(&self) -> Self {
+        match self {
+            Self::NorthEast => Self::NorthWest,
+            Self::East => Self::West,
+            Self::SouthEast => Self::SouthWest,
+            Self::SouthWest => Self::SouthEast,
+            Self::West => Self::East,
+            Self::NorthWest => Self::NorthEast,
+            Self::North | Self::South => *self,
+        }
+    }
+
+    /// The directions whose sprites can stand in for this one, from best to
+    /// worst, and whether each of them needs to be mirrored horizontally: this
+    /// direction, its mirror image, then the nearest horizontal and vertical
+    /// directions (horizontal first, as isometric diagonals are mostly horizontal).
+    pub fn fallbacks(&self) -> Vec<(Self, bool)> {
+        let mut fallbacks = vec![(*self, false)];
+        if self.mirrored() != *self {
+            fallbacks.push((self.mirrored(), true));
+        }
+        let (horizontal, vertical) = match self {
+            Self::NorthEast => (Some(Self::East), Some(Self::North)),
+            Self::SouthEast => (Some(Self::East), Some(Self::South)),
+            Self::SouthWest => (Some(Self::West), Some(Self::South)),
+            Self::NorthWest => (Some(Self::West), Some(Self::North)),
+            _ => (None, None),
+        };
+        if let Some(horizontal) = horizontal {
+            fallbacks.push((horizontal, false));
+            fallbacks.push((horizontal.mirrored(), true));
+        }
+        if let Some(vertical) = vertical {
+            fallbacks.push((vertical, false));
+        }
+        fallbacks
+    }
+}
+
+/// The direction a character faces.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Facing {
+    pub direction: CompassDirection,
+    /// Where the character is aiming, if anywhere. Takes precedence over movement.
+    pub aim: Vec2,
+}
+
+/// Face the aim direction, or the movement direction while moving. Characters
+/// whose movement is locked can't turn either.
+pub(super) fn update_facing(mut query: Query<(&mut Facing, Option<&MovementController>)>) {
+    for (mut facing, controller) in &mut query {
+        if controller.is_some_and(|controller| controller.locked) {
+            continue;
+        }
+        let intent = controller.map_or(Vec2::ZERO, |controller| controller.intent);
+        let target = if facing.aim != Vec2::ZERO {
+            facing.aim
+        } else {
+            intent
+        };
+        if let Some(direction) = CompassDirection::from_vector(target)
+            && facing.direction != direction
+        {
+            facing.direction = direction;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagonals_fall_back_to_their_mirror_then_horizontal_then_vertical() {
+        assert_eq!(
+            CompassDirection::NorthEast.fallbacks(),
+            [
+                (CompassDirection::NorthEast, false),
+                (CompassDirection::NorthWest, true),
+                (CompassDirection::East, false),
+                (CompassDirection::West, true),
+                (CompassDirection::North, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn horizontal_directions_fall_back_to_their_mirror() {
+        assert_eq!(
+            CompassDirection::West.fallbacks(),
+            [
+                (CompassDirection::West, false),
+                (CompassDirection::East, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn vertical_directions_have_no_fallbacks() {
+        assert_eq!(
+            CompassDirection::South.fallbacks(),
+            [(CompassDirection::South, false)]
+        );
+    }
+
+    #[test]
+    fn vectors_round_to_the_nearest_direction() {
+        assert_eq!(CompassDirection::from_vector(Vec2::ZERO), None);
+        assert_eq!(
+            CompassDirection::from_vector(Vec2::new(1.0, 0.3)),
+            Some(CompassDirection::East)
+        );
+        assert_eq!(
+            CompassDirection::from_vector(Vec2::new(-1.0, -0.9)),
+            Some(CompassDirection::SouthWest)
+        );
+        assert_eq!(
+            CompassDirection::from_vector(Vec2::new(0.2, 1.0)),
+            Some(CompassDirection::North)
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! Named flags that record what has happened in the game, e.g. which NPCs the
+//! player has talked to. Dialogue and quests set them, dialogue checks them,
+//! and they are part of the save game.
+
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GameFlags>();
+}
+
+/// The flags that are currently set.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct GameFlags(BTreeSet<String>);
+
+impl GameFlags {
+    pub fn is_set(&self, flag: &str) -> bool {
+        self.0.contains(flag)
+    }
+
+    pub fn set(&mut self, flag: impl Into<String>) {
+        self.0.insert(flag.into());
+    }
+
+    pub fn clear(&mut self, flag: &str) {
+        self.0.remove(flag);
+    }
+}
@@ -0,0 +1,335 @@
+//! Fog of war.
+//!
+//! Tiles start out hidden, and are revealed around every entity with [`Vision`],
+//! as far as it can see past collision tiles. Revealed tiles stay explored, and
+//! are drawn darker while out of sight. The explored tiles of every map are
+//! saved, so exploration carries over between runs.
+//!
+//! Objects spawned from the map are hidden on unexplored tiles. Enemies are
+//! also hidden on explored tiles that are out of sight, as they move around.
+
+use std::collections::{BTreeMap, HashSet};
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, Pause,
+    game::{
+        combat::Enemy,
+        tiled_map::{CollisionTiles, LoadedMapPath, MapObject, logical_tile},
+    },
+    screens::Screen,
+    storage,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(ExploredTiles::load());
+    app.init_resource::<VisibleTiles>();
+
+    app.add_systems(
+        Update,
+        (update_visible_tiles, (shade_tiles, hide_map_objects))
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update),
+    );
+
+    // Save whenever the game is paused or left.
+    app.add_systems(OnEnter(Pause(true)), save_explored_tiles);
+    app.add_systems(OnExit(Screen::Gameplay), save_explored_tiles);
+    app.add_systems(Last, save_explored_tiles.run_if(on_message::<AppExit>));
+}
+
+/// The storage key the explored tiles are saved under.
+const EXPLORED_TILES_KEY: &str = "exploration.ron";
+
+/// The color of explored tiles that are out of sight.
+const EXPLORED_SHADE: Color = Color::srgb(0.45, 0.45, 0.5);
+
+/// The color of tiles that haven't been explored yet.
+const UNEXPLORED_SHADE: Color = Color::BLACK;
+
+/// Reveals the tiles around an entity.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Vision {
+    /// How far the entity can see, in tiles.
+    pub radius: f32,
+}
+
+impl Default for Vision {
+    fn default() -> Self {
+        Self { radius: 6.0 }
+    }
+}
+
+/// The tiles that have ever been seen, by the asset path of their map.
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExploredTiles {
+    maps: BTreeMap<String, HashSet<IVec2>>,
+}
+
+impl ExploredTiles {
+    /// Whether a tile of the map at `map_path` has been seen.
+    pub fn is_explored(&self, map_path: &str, tile: IVec2) -> bool {
+        self.maps
+            .get(map_path)
+            .is_some_and(|tiles| tiles.contains(&tile))
+    }
+
+    /// Load the saved explored tiles, starting from scratch if there are none
+    /// or they can't be read.
+    fn load() -> Self {
+        let contents = match storage::read(EXPLORED_TILES_KEY) {
+            Ok(Some(contents)) => contents,
+            Ok(None) => return Self::default(),
+            Err(error) => {
+                warn!("Failed to read explored tiles: {error}");
+                return Self::default();
+            }
+        };
+        ron::from_str(&contents).unwrap_or_else(|error| {
+            warn!("Failed to parse explored tiles, starting from scratch: {error}");
+            Self::default()
+        })
+    }
+
+    fn save(&self) {
+        let contents = match ron::to_string(self) {
+            Ok(contents) => contents,
+            Err(error) => {
+                error!("Failed to serialize explored tiles: {error}");
+                return;
+            }
+        };
+        if let Err(error) = storage::write(EXPLORED_TILES_KEY, &contents) {
+            warn!("Failed to save explored tiles: {error}");
+        }
+    }
+}
+
+/// The tiles of the current map that are in sight right now.
+#[derive(Resource, Debug, Default)]
+pub struct VisibleTiles {
+    /// The map the tiles belong to.
+    map_path: Option<String>,
+    /// The tile and vision radius of every entity with [`Vision`], to only look
+    /// again once one of them changes.
+    viewers: Vec<(IVec2, f32)>,
+    tiles: HashSet<IVec2>,
+}
+
+impl VisibleTiles {
+    pub fn contains(&self, tile: IVec2) -> bool {
+        self.tiles.contains(&tile)
+    }
+}
+
+fn update_visible_tiles(
+    loaded_map: Res<LoadedMapPath>,
+    collisions: Res<CollisionTiles>,
+    viewer_query: Query<(&Vision, &GlobalTransform)>,
+    mut visible: ResMut<VisibleTiles>,
+    mut explored: ResMut<ExploredTiles>,
+) {
+    let Some(map_path) = &loaded_map.0 else {
+        return;
+    };
+    let viewers = viewer_query
+        .iter()
+        .map(|(vision, transform)| {
+            let tile = collisions.tile_at(transform.translation().xy());
+            (tile, vision.radius)
+        })
+        .collect::<Vec<_>>();
+    if visible.map_path.as_ref() == Some(map_path) && visible.viewers == viewers {
+        return;
+    }
+
+    let mut tiles = HashSet::new();
+    for &(origin, radius) in &viewers {
+        reveal(&collisions, origin, radius, &mut tiles);
+    }
+
+    // Only mark the explored tiles as changed when new ones were found, as the
+    // minimap is redrawn when they change.
+    let explored_tiles = explored
+        .bypass_change_detection()
+        .maps
+        .entry(map_path.clone())
+        .or_default();
+    let explored_count = explored_tiles.len();
+    explored_tiles.extend(&tiles);
+    if explored_tiles.len() != explored_count {
+        explored.set_changed();
+    }
+
+    *visible = VisibleTiles {
+        map_path: Some(map_path.clone()),
+        viewers,
+        tiles,
+    };
+}
+
+/// Add every tile within `radius` of `origin` that can be seen from it to
+/// `tiles`. Collision tiles can be seen, but block the view of what is behind
+/// them.
+fn reveal(collisions: &CollisionTiles, origin: IVec2, radius: f32, tiles: &mut HashSet<IVec2>) {
+    let reach = radius.ceil() as i32;
+    for y in -reach..=reach {
+        for x in -reach..=reach {
+            let offset = IVec2::new(x, y);
+            let tile = origin + offset;
+            if offset.as_vec2().length() > radius || !collisions.in_map(tile) {
+                continue;
+            }
+            if line_of_sight(collisions, origin, tile) {
+                tiles.insert(tile);
+            }
+        }
+    }
+}
+
+/// Whether none of the tiles between `from` and `to` are collision tiles.
+fn line_of_sight(collisions: &CollisionTiles, from: IVec2, to: IVec2) -> bool {
+    // Bresenham's line algorithm.
+    let delta = (to - from).abs();
+    let step = (to - from).signum();
+    let mut error = delta.x - delta.y;
+    let mut tile = from;
+    while tile != to {
+        if tile != from && collisions.blocked.contains(&tile) {
+            return false;
+        }
+        let doubled = 2 * error;
+        if doubled > -delta.y {
+            error -= delta.y;
+            tile.x += step.x;
+        }
+        if doubled < delta.x {
+            error += delta.x;
+            tile.y += step.y;
+        }
+    }
+    true
+}
+
+/// Color the tiles by whether they are in sight, explored or unexplored.
+fn shade_tiles(
+    loaded_map: Res<LoadedMapPath>,
+    collisions: Res<CollisionTiles>,
+    visible: Res<VisibleTiles>,
+    explored: Res<ExploredTiles>,
+    mut tile_query: Query<(Ref<TilePos>, &mut TileColor)>,
+) {
+    let Some(map_path) = &loaded_map.0 else {
+        return;
+    };
+    for (tile_pos, mut color) in &mut tile_query {
+        // Newly spawned tiles need shading too, e.g. when the map is reloaded.
+        if !visible.is_changed() && !tile_pos.is_added() {
+            continue;
+        }
+        let tile = logical_tile(*tile_pos, collisions.map_size.x);
+        let shade = if visible.contains(tile) {
+            Color::WHITE
+        } else if explored.is_explored(map_path, tile) {
+            EXPLORED_SHADE
+        } else {
+            UNEXPLORED_SHADE
+        };
+        if color.0 != shade {
+            color.0 = shade;
+        }
+    }
+}
+
+/// Show map objects by whether their tile is in sight, explored or unexplored.
+fn hide_map_objects(
+    loaded_map: Res<LoadedMapPath>,
+    collisions: Res<CollisionTiles>,
+    visible: Res<VisibleTiles>,
+    explored: Res<ExploredTiles>,
+    mut object_query: Query<
+        (&GlobalTransform, &mut Visibility, Has<Enemy>),
+        (With<MapObject>, With<Sprite>),
+    >,
+) {
+    let Some(map_path) = &loaded_map.0 else {
+        return;
+    };
+    for (transform, mut visibility, is_enemy) in &mut object_query {
+        let tile = collisions.tile_at(transform.translation().xy());
+        let shown = visible.contains(tile)
+            || (!is_enemy && explored.is_explored(map_path, tile));
+        visibility.set_if_neq(if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+fn save_explored_tiles(explored: Res<ExploredTiles>) {
+    explored.save();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(blocked: &[IVec2]) -> CollisionTiles {
+        CollisionTiles {
+            blocked: blocked.iter().copied().collect(),
+            map_size: UVec2::splat(10),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn open_ground_is_in_sight() {
+        let collisions = map(&[]);
+        assert!(line_of_sight(&collisions, IVec2::new(0, 0), IVec2::new(7, 3)));
+        assert!(line_of_sight(&collisions, IVec2::new(7, 3), IVec2::new(0, 0)));
+    }
+
+    #[test]
+    fn collision_tiles_can_be_seen_but_block_what_is_behind_them() {
+        let collisions = map(&[IVec2::new(2, 0)]);
+        assert!(line_of_sight(&collisions, IVec2::new(0, 0), IVec2::new(2, 0)));
+        assert!(!line_of_sight(&collisions, IVec2::new(0, 0), IVec2::new(4, 0)));
+        // Standing on a collision tile doesn't block the view.
+        assert!(line_of_sight(&collisions, IVec2::new(2, 0), IVec2::new(4, 0)));
+    }
+
+    #[test]
+    fn reveal_covers_a_circle_of_tiles() {
+        let mut tiles = HashSet::new();
+        reveal(&map(&[]), IVec2::new(5, 5), 2.0, &mut tiles);
+        assert_eq!(tiles.len(), 13);
+        assert!(tiles.contains(&IVec2::new(6, 6)));
+        assert!(!tiles.contains(&IVec2::new(7, 7)));
+    }
+
+    #[test]
+    fn reveal_stops_at_the_map_edge() {
+        let mut tiles = HashSet::new();
+        reveal(&map(&[]), IVec2::ZERO, 1.0, &mut tiles);
+        assert_eq!(
+            tiles,
+            HashSet::from([IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(0, 1)])
+        );
+    }
+
+    #[test]
+    fn reveal_stops_behind_collision_tiles() {
+        let mut tiles = HashSet::new();
+        reveal(&map(&[IVec2::new(6, 5)]), IVec2::new(5, 5), 3.0, &mut tiles);
+        assert!(tiles.contains(&IVec2::new(6, 5)));
+        assert!(!tiles.contains(&IVec2::new(7, 5)));
+        assert!(!tiles.contains(&IVec2::new(8, 5)));
+    }
+}
@@ -0,0 +1,194 @@
+//! Footstep sounds that depend on the surface a character is walking on.
+//!
+//! Trigger [`Footstep`] on a character with [`Footsteps`] whenever one of its
+//! feet hits the ground. Animated characters do this with `footstep` events on
+//! the frames of their clips (see [`AnimationEvent`]). Characters without a
+//! [`SpriteAnimator`], like NPCs and enemies, take a step every stride they move.
+
+use std::collections::HashMap;
+
+use bevy::{audio::Volume, prelude::*};
+use rand::Rng;
+
+use crate::{
+    AppSystems, PausableSystems,
+    asset_tracking::LoadResource,
+    audio::spatial_sound_effect,
+    game::{
+        sprite_animation::{AnimationEvent, SpriteAnimator},
+        tiled_map::{Surface, SurfaceTiles},
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.load_resource::<FootstepAssets>();
+    app.add_observer(trigger_footstep_from_animation);
+    app.add_observer(play_footstep);
+    app.add_systems(
+        Update,
+        trigger_footsteps_from_movement
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// How far a character without a [`SpriteAnimator`] moves between footsteps.
+const STRIDE: f32 = 20.0;
+
+/// Moving further than this in a single frame is a jump to another place, e.g.
+/// through a door, rather than walking.
+const MAX_FRAME_DISTANCE: f32 = 32.0;
+
+/// A character that makes footstep sounds.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Footsteps {
+    /// The surface and index of the last sound played, so that it isn't played
+    /// twice in a row.
+    last: Option<(Surface, usize)>,
+    /// Where the character was last frame, and how far it has moved since its
+    /// last footstep. Only used without a [`SpriteAnimator`].
+    last_position: Option<Vec2>,
+    distance: f32,
+}
+
+/// Play a footstep sound for the surface under the character.
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct Footstep {
+    pub entity: Entity,
+}
+
+/// The sounds for a single [`Surface`].
+#[derive(Debug, Clone, Reflect)]
+pub struct FootstepSet {
+    pub sounds: Vec<Handle<AudioSource>>,
+    /// The base volume, varied randomly by up to `volume_variation` either way.
+    pub volume: f32,
+    pub volume_variation: f32,
+    /// The base playback speed (and so pitch), varied randomly by up to
+    /// `pitch_variation` either way.
+    pub pitch: f32,
+    pub pitch_variation: f32,
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct FootstepAssets {
+    /// Every sound in `sets`, so that loading waits for all of them.
+    #[dependency]
+    sounds: Vec<Handle<AudioSource>>,
+    sets: HashMap<Surface, FootstepSet>,
+}
+
+impl FromWorld for FootstepAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        // Each surface has its own recordings, `{name}1.ogg` to `{name}4.ogg`.
+        let set = |name: &str, volume| FootstepSet {
+            sounds: (1..=4)
+                .map(|i| assets.load(format!("audio/sound_effects/{name}{i}.ogg")))
+                .collect(),
+            volume,
+            volume_variation: 0.15,
+            pitch: 1.0,
+            pitch_variation: 0.08,
+        };
+        let sets = HashMap::from([
+            (Surface::Default, set("step", 1.0)),
+            (Surface::Grass, set("grass_step", 0.9)),
+            (Surface::Stone, set("stone_step", 1.0)),
+            (Surface::Water, set("water_step", 0.9)),
+        ]);
+        Self {
+            sounds: sets.values().flat_map(|set| set.sounds.clone()).collect(),
+            sets,
+        }
+    }
+}
+
+impl FootstepAssets {
+    fn set(&self, surface: Surface) -> &FootstepSet {
+        self.sets
+            .get(&surface)
+            .unwrap_or_else(|| &self.sets[&Surface::Default])
+    }
+}
+
+fn trigger_footstep_from_animation(animation_event: On<AnimationEvent>, mut commands: Commands) {
+    if animation_event.name == "footstep" {
+        commands.trigger(Footstep {
+            entity: animation_event.entity,
+        });
+    }
+}
+
+fn trigger_footsteps_from_movement(
+    mut commands: Commands,
+    mut characters: Query<(Entity, &GlobalTransform, &mut Footsteps), Without<SpriteAnimator>>,
+) {
+    for (entity, transform, mut footsteps) in &mut characters {
+        let position = transform.translation().xy();
+        let Some(last_position) = footsteps.last_position.replace(position) else {
+            continue;
+        };
+        let moved = position.distance(last_position);
+        if moved > MAX_FRAME_DISTANCE {
+            footsteps.distance = 0.0;
+            continue;
+        }
+        footsteps.distance += moved;
+        if footsteps.distance >= STRIDE {
+            footsteps.distance -= STRIDE;
+            commands.trigger(Footstep { entity });
+        }
+    }
+}
+
+fn play_footstep(
+    footstep: On<Footstep>,
+    mut commands: Commands,
+    footstep_assets: Option<Res<FootstepAssets>>,
+    surfaces: Res<SurfaceTiles>,
+    mut characters: Query<(&GlobalTransform, &mut Footsteps)>,
+) {
+    let Some(footstep_assets) = footstep_assets else {
+        return;
+    };
+    let Ok((transform, mut footsteps)) = characters.get_mut(footstep.entity) else {
+        return;
+    };
+
+    let translation = transform.translation();
+    let surface = surfaces.surface_at(translation.xy());
+    let set = footstep_assets.set(surface);
+    if set.sounds.is_empty() {
+        return;
+    }
+
+    // Pick a random sound, skipping the last one played on this surface.
+    let rng = &mut rand::rng();
+    let last = footsteps
+        .last
+        .filter(|(last_surface, _)| *last_surface == surface && set.sounds.len() > 1)
+        .map(|(_, index)| index);
+    let mut index = rng.random_range(0..set.sounds.len() - usize::from(last.is_some()));
+    if last.is_some_and(|last| index >= last) {
+        index += 1;
+    }
+    footsteps.last = Some((surface, index));
+
+    let volume = set.volume + rng.random_range(-1.0..=1.0) * set.volume_variation;
+    let pitch = set.pitch + rng.random_range(-1.0..=1.0) * set.pitch_variation;
+    commands
+        .spawn((
+            Name::new("Footstep"),
+            spatial_sound_effect(set.sounds[index].clone(), translation),
+        ))
+        // Vary the volume and pitch of the default playback settings.
+        .insert(
+            PlaybackSettings::DESPAWN
+                .with_spatial(true)
+                .with_volume(Volume::Linear(volume.max(0.0)))
+                .with_speed(pitch.max(0.1)),
+        );
+}
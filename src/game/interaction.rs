@@ -0,0 +1,162 @@
+//! Things the player can interact with, like NPCs, chests, doors and signs.
+//!
+//! While the player is within an [`Interactable`]'s radius and facing it, a
+//! prompt is shown above it, and [`InputAction::Interact`] triggers [`Interact`]
+//! on it for its observers to handle.
+
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, Pause, PausableSystems,
+    game::{facing::Facing, player::Player},
+    input::{InputAction, InputBindings, LastInputDevice, action_just_pressed},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<InteractionTarget>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_interaction_prompt);
+    app.add_systems(OnEnter(Pause(true)), clear_interaction_target);
+    app.add_systems(OnExit(Screen::Gameplay), clear_interaction_target);
+    app.add_systems(
+        Update,
+        (
+            interact
+                .run_if(action_just_pressed(InputAction::Interact))
+                .in_set(AppSystems::RecordInput)
+                .in_set(PausableSystems),
+            (
+                update_interaction_target.in_set(PausableSystems),
+                update_interaction_prompt,
+            )
+                .chain()
+                .in_set(AppSystems::Update),
+        ),
+    );
+}
+
+/// How far to the side of where the player is facing an interactable can be,
+/// as the cosine of the angle between them (60°).
+const MIN_FACING_ALIGNMENT: f32 = 0.5;
+
+/// How high above an interactable its prompt is shown.
+const PROMPT_HEIGHT: f32 = 14.0;
+
+/// Something the player can interact with. Observe [`Interact`] on the entity
+/// to do something when they do.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Interactable {
+    /// How close the player has to be.
+    pub radius: f32,
+    /// What interacting does, shown in the prompt, e.g. `Talk`.
+    pub prompt: String,
+}
+
+impl Interactable {
+    pub fn new(radius: f32, prompt: impl Into<String>) -> Self {
+        Self {
+            radius,
+            prompt: prompt.into(),
+        }
+    }
+}
+
+/// The player interacted with an [`Interactable`].
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct Interact {
+    pub entity: Entity,
+    /// The entity doing the interacting.
+    pub by: Entity,
+}
+
+/// The [`Interactable`] the player would interact with right now, if any.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InteractionTarget(pub Option<Entity>);
+
+/// The prompt shown above the [`InteractionTarget`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct InteractionPrompt;
+
+fn spawn_interaction_prompt(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Interaction Prompt"),
+        InteractionPrompt,
+        Text2d::default(),
+        TextFont::from_font_size(8.0),
+        TextColor(Color::WHITE),
+        Transform::default(),
+        Visibility::Hidden,
+        DespawnOnExit(Screen::Gameplay),
+    ));
+}
+
+/// Pick the closest interactable in range that the player is facing.
+fn update_interaction_target(
+    player: Single<(&GlobalTransform, &Facing), With<Player>>,
+    interactable_query: Query<(Entity, &Interactable, &GlobalTransform)>,
+    mut target: ResMut<InteractionTarget>,
+) {
+    let (player_transform, facing) = player.into_inner();
+    let position = player_transform.translation().xy();
+    let forward = facing.direction.vector();
+
+    let closest = interactable_query
+        .iter()
+        .filter_map(|(entity, interactable, transform)| {
+            let offset = transform.translation().xy() - position;
+            let distance = offset.length();
+            let facing_it = offset
+                .try_normalize()
+                .is_none_or(|direction| direction.dot(forward) >= MIN_FACING_ALIGNMENT);
+            (distance <= interactable.radius && facing_it).then_some((entity, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity);
+    target.set_if_neq(InteractionTarget(closest));
+}
+
+fn update_interaction_prompt(
+    target: Res<InteractionTarget>,
+    bindings: Res<InputBindings>,
+    last_device: Res<LastInputDevice>,
+    interactable_query: Query<(&Interactable, &GlobalTransform)>,
+    prompt: Single<(&mut Text2d, &mut Transform, &mut Visibility), With<InteractionPrompt>>,
+) {
+    let (mut text, mut transform, mut visibility) = prompt.into_inner();
+    let Some((interactable, interactable_transform)) =
+        target.0.and_then(|entity| interactable_query.get(entity).ok())
+    else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    let key = bindings.get(InputAction::Interact).describe(last_device.0);
+    let prompt_text = format!("[{key}] {}", interactable.prompt);
+    if text.0 != prompt_text {
+        text.0 = prompt_text;
+    }
+    // Above everything else in the world.
+    let position = interactable_transform.translation().xy() + Vec2::Y * PROMPT_HEIGHT;
+    transform.translation = position.extend(100.0);
+    visibility.set_if_neq(Visibility::Inherited);
+}
+
+fn interact(
+    mut commands: Commands,
+    target: Res<InteractionTarget>,
+    player: Single<Entity, With<Player>>,
+) {
+    if let Some(entity) = target.0 {
+        commands.trigger(Interact {
+            entity,
+            by: *player,
+        });
+    }
+}
+
+fn clear_interaction_target(mut target: ResMut<InteractionTarget>) {
+    target.set_if_neq(InteractionTarget(None));
+}
@@ -0,0 +1,423 @@
+//! Inventories, equipment and item pickups.
+//!
+//! Entities with an [`Inventory`] collect [`Pickup`]s they walk over, and are
+//! given items with [`GiveItem`]. Items are used, equipped, unequipped and
+//! dropped by triggering [`UseItem`], [`EquipItem`], [`UnequipItem`] and
+//! [`DropItem`] on the entity. The
+//! modifiers of equipped items and used items change its
+//! [`Stats`](crate::game::stats::Stats).
+
+use std::{collections::BTreeMap, time::Duration};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        items::{EquipmentSlot, Items},
+        stats::{Buffs, EquipmentModifiers},
+        tiled_map::{MapObject, TiledMapHandle},
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(give_item);
+    app.add_observer(use_item);
+    app.add_observer(equip_item);
+    app.add_observer(unequip_item);
+    app.add_observer(drop_item);
+
+    app.add_systems(
+        Update,
+        (collect_pickups, update_equipment_modifiers)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// How many stacks of items an [`Inventory`] holds.
+pub const INVENTORY_CAPACITY: usize = 20;
+
+/// How close an entity has to be to a [`Pickup`] to collect it.
+const PICKUP_RADIUS: f32 = 12.0;
+
+const PICKUP_SIZE: f32 = 8.0;
+const PICKUP_COLOR: Color = Color::srgb(0.95, 0.8, 0.3);
+
+/// A number of the same item.
+#[derive(Debug, Clone, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub struct ItemStack {
+    /// The id of the item's [`ItemDefinition`](crate::game::items::ItemDefinition).
+    pub item: String,
+    pub count: u32,
+}
+
+/// The items an entity carries, and the ones it has equipped.
+#[derive(Component, Debug, Clone, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+#[serde(default)]
+pub struct Inventory {
+    stacks: Vec<ItemStack>,
+    equipped: BTreeMap<EquipmentSlot, String>,
+}
+
+impl Inventory {
+    pub fn stacks(&self) -> &[ItemStack] {
+        &self.stacks
+    }
+
+    /// The id of the item equipped in each slot.
+    pub fn equipped(&self) -> &BTreeMap<EquipmentSlot, String> {
+        &self.equipped
+    }
+
+    /// Add `count` of an item, filling up existing stacks before starting new
+    /// ones. Returns how many didn't fit.
+    pub fn add(&mut self, item: &str, count: u32, max_stack: u32) -> u32 {
+        let mut remaining = count;
+        for stack in self.stacks.iter_mut().filter(|stack| stack.item == item) {
+            let added = remaining.min(max_stack.saturating_sub(stack.count));
+            stack.count += added;
+            remaining -= added;
+        }
+        while remaining > 0 && self.stacks.len() < INVENTORY_CAPACITY {
+            let added = remaining.min(max_stack);
+            self.stacks.push(ItemStack {
+                item: item.to_string(),
+                count: added,
+            });
+            remaining -= added;
+        }
+        remaining
+    }
+
+    /// Take one item from the stack at `index`, removing the stack once it is empty.
+    fn take_one(&mut self, index: usize) -> Option<String> {
+        let stack = self.stacks.get_mut(index)?;
+        stack.count -= 1;
+        let item = stack.item.clone();
+        if stack.count == 0 {
+            self.stacks.remove(index);
+        }
+        Some(item)
+    }
+}
+
+/// An item lying in the world, collected by the first entity with an
+/// [`Inventory`] to get close to it.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Pickup {
+    pub stack: ItemStack,
+    /// The entity that dropped the item, which can't collect it again until it
+    /// has moved away from it.
+    pub dropped_by: Option<Entity>,
+}
+
+impl Pickup {
+    pub fn new(stack: ItemStack) -> Self {
+        Self {
+            stack,
+            dropped_by: None,
+        }
+    }
+}
+
+/// An item lying in the world at `position`, e.g. placed in a map or dropped
+/// by an enemy.
+pub fn pickup(pickup: Pickup, position: Vec2) -> impl Bundle {
+    (
+        Name::new(format!("Pickup {}", pickup.stack.item)),
+        pickup,
+        Sprite::from_color(PICKUP_COLOR, Vec2::splat(PICKUP_SIZE)),
+        // Just below characters.
+        Transform::from_translation(position.extend(2.0)),
+    )
+}
+
+/// Put a number of an item into an entity's [`Inventory`]. Items that don't fit
+/// are dropped at its feet.
+#[derive(EntityEvent, Debug, Clone)]
+pub struct GiveItem {
+    pub entity: Entity,
+    pub stack: ItemStack,
+}
+
+/// Use up one of the items in a stack of an entity's [`Inventory`]. Does nothing
+/// if the item can't be used.
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct UseItem {
+    pub entity: Entity,
+    /// The index of the stack in [`Inventory::stacks`].
+    pub index: usize,
+}
+
+/// Equip one of the items in a stack of an entity's [`Inventory`], putting back
+/// the item it replaces. Does nothing if the item can't be equipped.
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct EquipItem {
+    pub entity: Entity,
+    /// The index of the stack in [`Inventory::stacks`].
+    pub index: usize,
+}
+
+/// Put the item equipped in a slot back into an entity's [`Inventory`]. Does
+/// nothing if there is no room for it.
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct UnequipItem {
+    pub entity: Entity,
+    pub slot: EquipmentSlot,
+}
+
+/// Drop a whole stack of an entity's [`Inventory`] at its feet.
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct DropItem {
+    pub entity: Entity,
+    /// The index of the stack in [`Inventory::stacks`].
+    pub index: usize,
+}
+
+/// Spawn a pickup at a world position on the current map, so that it goes away
+/// with the map's other objects.
+fn drop_on_map(
+    commands: &mut Commands,
+    map_query: &Query<(Entity, &GlobalTransform), With<TiledMapHandle>>,
+    dropped: Pickup,
+    position: Vec2,
+) {
+    let Ok((map_entity, map_transform)) = map_query.single() else {
+        warn!("Can't drop `{}` without a map", dropped.stack.item);
+        return;
+    };
+    let local = map_transform
+        .affine()
+        .inverse()
+        .transform_point3(position.extend(0.0));
+    commands.spawn((pickup(dropped, local.xy()), MapObject, ChildOf(map_entity)));
+}
+
+fn give_item(
+    give: On<GiveItem>,
+    mut commands: Commands,
+    items: Items,
+    mut query: Query<(&mut Inventory, &GlobalTransform)>,
+    map_query: Query<(Entity, &GlobalTransform), With<TiledMapHandle>>,
+) {
+    let Ok((mut inventory, transform)) = query.get_mut(give.entity) else {
+        return;
+    };
+    let Some(definition) = items.get(&give.stack.item) else {
+        warn!("Can't give unknown item `{}`", give.stack.item);
+        return;
+    };
+    let remaining = inventory.add(&give.stack.item, give.stack.count, definition.max_stack);
+    if remaining > 0 {
+        drop_on_map(
+            &mut commands,
+            &map_query,
+            Pickup {
+                stack: ItemStack {
+                    item: give.stack.item.clone(),
+                    count: remaining,
+                },
+                dropped_by: Some(give.entity),
+            },
+            transform.translation().xy(),
+        );
+    }
+}
+
+fn use_item(used: On<UseItem>, items: Items, mut query: Query<(&mut Inventory, &mut Buffs)>) {
+    let Ok((mut inventory, mut buffs)) = query.get_mut(used.entity) else {
+        return;
+    };
+    let Some(consume) = inventory
+        .stacks
+        .get(used.index)
+        .and_then(|stack| items.get(&stack.item))
+        .and_then(|definition| definition.consume.clone())
+    else {
+        return;
+    };
+    inventory.take_one(used.index);
+    buffs.add(consume.modifiers, Duration::from_secs_f32(consume.duration_secs));
+}
+
+fn equip_item(equip: On<EquipItem>, items: Items, mut inventory_query: Query<&mut Inventory>) {
+    let Ok(mut inventory) = inventory_query.get_mut(equip.entity) else {
+        return;
+    };
+    let Some((slot, max_stack)) = inventory
+        .stacks
+        .get(equip.index)
+        .and_then(|stack| items.get(&stack.item))
+        .and_then(|definition| Some((definition.equip.as_ref()?.slot, definition.max_stack)))
+    else {
+        return;
+    };
+    let Some(item) = inventory.take_one(equip.index) else {
+        return;
+    };
+    let Some(replaced) = inventory.equipped.insert(slot, item) else {
+        return;
+    };
+    let replaced_max_stack = items.get(&replaced).map_or(1, |definition| definition.max_stack);
+    if inventory.add(&replaced, 1, replaced_max_stack) > 0 {
+        // No room for the replaced item, so swap back. There is room for the
+        // other one, as it just came out of the inventory.
+        if let Some(item) = inventory.equipped.insert(slot, replaced) {
+            inventory.add(&item, 1, max_stack);
+        }
+    }
+}
+
+fn unequip_item(
+    unequip: On<UnequipItem>,
+    items: Items,
+    mut inventory_query: Query<&mut Inventory>,
+) {
+    let Ok(mut inventory) = inventory_query.get_mut(unequip.entity) else {
+        return;
+    };
+    let Some(item) = inventory.equipped.get(&unequip.slot).cloned() else {
+        return;
+    };
+    let max_stack = items.get(&item).map_or(1, |definition| definition.max_stack);
+    if inventory.add(&item, 1, max_stack) == 0 {
+        inventory.equipped.remove(&unequip.slot);
+    }
+}
+
+fn drop_item(
+    dropped: On<DropItem>,
+    mut commands: Commands,
+    mut query: Query<(&mut Inventory, &GlobalTransform)>,
+    map_query: Query<(Entity, &GlobalTransform), With<TiledMapHandle>>,
+) {
+    let Ok((mut inventory, transform)) = query.get_mut(dropped.entity) else {
+        return;
+    };
+    if dropped.index >= inventory.stacks.len() {
+        return;
+    }
+    let stack = inventory.stacks.remove(dropped.index);
+    drop_on_map(
+        &mut commands,
+        &map_query,
+        Pickup {
+            stack,
+            dropped_by: Some(dropped.entity),
+        },
+        transform.translation().xy(),
+    );
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    items: Items,
+    mut collector_query: Query<(Entity, &GlobalTransform, &mut Inventory)>,
+    mut pickup_query: Query<(Entity, &GlobalTransform, &mut Pickup)>,
+) {
+    for (pickup_entity, pickup_transform, mut pickup) in &mut pickup_query {
+        let position = pickup_transform.translation().xy();
+        for (collector, transform, mut inventory) in &mut collector_query {
+            let in_reach = transform.translation().xy().distance(position) <= PICKUP_RADIUS;
+            if pickup.dropped_by == Some(collector) {
+                if !in_reach {
+                    pickup.dropped_by = None;
+                }
+                continue;
+            }
+            if !in_reach {
+                continue;
+            }
+
+            let Some(definition) = items.get(&pickup.stack.item) else {
+                warn!("Unknown item `{}`, removing its pickup", pickup.stack.item);
+                commands.entity(pickup_entity).despawn();
+                break;
+            };
+            let remaining =
+                inventory.add(&pickup.stack.item, pickup.stack.count, definition.max_stack);
+            if remaining == 0 {
+                commands.entity(pickup_entity).despawn();
+                break;
+            }
+            if remaining != pickup.stack.count {
+                pickup.stack.count = remaining;
+            }
+        }
+    }
+}
+
+/// Collect the modifiers of equipped items, for the entity's stats.
+fn update_equipment_modifiers(
+    items: Items,
+    mut query: Query<(&Inventory, &mut EquipmentModifiers), Changed<Inventory>>,
+) {
+    for (inventory, mut equipment_modifiers) in &mut query {
+        let modifiers = inventory
+            .equipped
+            .values()
+            .filter_map(|item| items.get(item))
+            .filter_map(|definition| definition.equip.as_ref())
+            .flat_map(|equip| equip.modifiers.iter().copied())
+            .collect();
+        equipment_modifiers.set_if_neq(EquipmentModifiers(modifiers));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(inventory: &Inventory) -> Vec<(&str, u32)> {
+        inventory
+            .stacks()
+            .iter()
+            .map(|stack| (stack.item.as_str(), stack.count))
+            .collect()
+    }
+
+    #[test]
+    fn adding_fills_existing_stacks_before_starting_new_ones() {
+        let mut inventory = Inventory::default();
+        assert_eq!(inventory.add("potion", 3, 5), 0);
+        assert_eq!(inventory.add("sword", 1, 1), 0);
+        assert_eq!(inventory.add("potion", 4, 5), 0);
+        assert_eq!(
+            counts(&inventory),
+            [("potion", 5), ("sword", 1), ("potion", 2)]
+        );
+    }
+
+    #[test]
+    fn adding_splits_large_counts_into_full_stacks() {
+        let mut inventory = Inventory::default();
+        assert_eq!(inventory.add("arrow", 12, 5), 0);
+        assert_eq!(
+            counts(&inventory),
+            [("arrow", 5), ("arrow", 5), ("arrow", 2)]
+        );
+    }
+
+    #[test]
+    fn adding_to_a_full_inventory_returns_what_did_not_fit() {
+        let mut inventory = Inventory::default();
+        let capacity = INVENTORY_CAPACITY as u32;
+        assert_eq!(inventory.add("rock", capacity + 2, 1), 2);
+        assert_eq!(inventory.stacks().len(), INVENTORY_CAPACITY);
+        assert_eq!(inventory.add("potion", 1, 5), 1);
+    }
+
+    #[test]
+    fn a_full_inventory_still_tops_up_its_stacks() {
+        let mut inventory = Inventory::default();
+        inventory.add("potion", 3, 5);
+        inventory.add("rock", INVENTORY_CAPACITY as u32 - 1, 1);
+        assert_eq!(inventory.add("potion", 4, 5), 2);
+        assert_eq!(counts(&inventory)[0], ("potion", 5));
+    }
+}
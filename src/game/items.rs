@@ -0,0 +1,178 @@
+//! Item definitions described in `.items.ron` asset files.
+//!
+//! Every item has an id, which inventories and pickups refer to it by, and a
+//! definition saying how many fit in one inventory slot and what it does when
+//! equipped or used:
+//!
+//! ```ron
+//! (
+//!     items: {
+//!         "swift_boots": (
+//!             name: "Swift Boots",
+//!             description: "Light boots that quicken your step.",
+//!             equip: Some((slot: Feet, modifiers: [Multiply(Speed, 1.2)])),
+//!         ),
+//!         "haste_potion": (
+//!             name: "Haste Potion",
+//!             description: "Run faster for a short while.",
+//!             max_stack: 5,
+//!             consume: Some((modifiers: [Multiply(Speed, 1.5)], duration_secs: 10.0)),
+//!         ),
+//!     },
+//! )
+//! ```
+//!
+//! See the [`stats`](crate::game::stats) module for how modifiers stack.
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{asset_tracking::LoadResource, game::stats::StatModifier};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<ItemDefinitions>();
+    app.register_asset_loader(ItemDefinitionsLoader);
+    app.load_resource::<ItemAssets>();
+}
+
+/// Where an equipped item is worn. Each slot holds one item.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect, Serialize, Deserialize,
+)]
+pub enum EquipmentSlot {
+    Head,
+    Body,
+    Hands,
+    Feet,
+}
+
+/// What an item does while it is equipped.
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct EquipEffect {
+    pub slot: EquipmentSlot,
+    #[serde(default)]
+    pub modifiers: Vec<StatModifier>,
+}
+
+/// What an item does when it is used up.
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct ConsumeEffect {
+    #[serde(default)]
+    pub modifiers: Vec<StatModifier>,
+    /// How long the modifiers last.
+    pub duration_secs: f32,
+}
+
+/// A single item in an [`ItemDefinitions`] asset.
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct ItemDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// How many of the item fit in one inventory slot.
+    #[serde(default = "one")]
+    pub max_stack: u32,
+    /// Set for items that can be equipped.
+    #[serde(default)]
+    pub equip: Option<EquipEffect>,
+    /// Set for items that can be used.
+    #[serde(default)]
+    pub consume: Option<ConsumeEffect>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// A set of [`ItemDefinition`]s by id, loaded from a `.items.ron` file.
+#[derive(Asset, Debug, Reflect, Deserialize)]
+pub struct ItemDefinitions {
+    items: HashMap<String, ItemDefinition>,
+}
+
+#[derive(Debug, Error)]
+pub enum ItemDefinitionsLoaderError {
+    #[error("Could not load item file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse item file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Item `{0}` has a max stack of zero")]
+    EmptyStack(String),
+    #[error("Item `{0}` can be both equipped and used")]
+    EquipAndConsume(String),
+}
+
+pub struct ItemDefinitionsLoader;
+
+impl AssetLoader for ItemDefinitionsLoader {
+    type Asset = ItemDefinitions;
+    type Settings = ();
+    type Error = ItemDefinitionsLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let definitions: ItemDefinitions = ron::de::from_bytes(&bytes)?;
+
+        for (id, item) in &definitions.items {
+            if item.max_stack == 0 {
+                return Err(ItemDefinitionsLoaderError::EmptyStack(id.clone()));
+            }
+            if item.equip.is_some() && item.consume.is_some() {
+                return Err(ItemDefinitionsLoaderError::EquipAndConsume(id.clone()));
+            }
+        }
+        Ok(definitions)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["items.ron"]
+    }
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct ItemAssets {
+    #[dependency]
+    pub definitions: Handle<ItemDefinitions>,
+}
+
+impl FromWorld for ItemAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            definitions: assets.load("items/base.items.ron"),
+        }
+    }
+}
+
+/// Looks up [`ItemDefinition`]s by id.
+#[derive(SystemParam)]
+pub struct Items<'w> {
+    item_assets: Option<Res<'w, ItemAssets>>,
+    definitions: Res<'w, Assets<ItemDefinitions>>,
+}
+
+impl Items<'_> {
+    /// The definition of an item, or `None` if there is no such item or the
+    /// definitions haven't been loaded yet.
+    pub fn get(&self, id: &str) -> Option<&ItemDefinition> {
+        let item_assets = self.item_assets.as_ref()?;
+        self.definitions
+            .get(&item_assets.definitions)?
+            .items
+            .get(id)
+    }
+}
@@ -3,8 +3,7 @@
 use bevy::prelude::*;
 
 use crate::{
-    asset_tracking::LoadResource,
-    audio::music,
+    audio::director::{MusicAssets, MusicDirector},
     game::{
         map::{MapAssets, map},
         player::{PlayerAssets, player},
@@ -13,32 +12,16 @@ use crate::{
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.load_resource::<LevelAssets>();
-}
-
-#[derive(Resource, Asset, Clone, Reflect)]
-#[reflect(Resource)]
-pub struct LevelAssets {
-    #[dependency]
-    music: Handle<AudioSource>,
-}
-
-impl FromWorld for LevelAssets {
-    fn from_world(world: &mut World) -> Self {
-        let assets = world.resource::<AssetServer>();
-        Self {
-            music: assets.load("audio/music/Fluffing A Duck.ogg"),
-        }
-    }
+    app.add_systems(OnExit(Screen::Gameplay), stop_gameplay_music);
 }
 
 /// A system that spawns the main level.
 pub fn spawn_level(
     mut commands: Commands,
-    level_assets: Res<LevelAssets>,
+    music_assets: Res<MusicAssets>,
+    mut music_director: ResMut<MusicDirector>,
     player_assets: Res<PlayerAssets>,
     map_assets: Res<MapAssets>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
     commands.spawn((
         Name::new("Level"),
@@ -47,11 +30,13 @@ pub fn spawn_level(
         DespawnOnExit(Screen::Gameplay),
         children![
             map(&map_assets),
-            player(200.0, &player_assets, &mut texture_atlas_layouts),
-            (
-                Name::new("Gameplay Music"),
-                music(level_assets.music.clone())
-            )
+            player(200.0, &player_assets),
         ],
     ));
+
+    music_director.play(music_assets.playlist().shuffled());
+}
+
+fn stop_gameplay_music(mut music_director: ResMut<MusicDirector>) {
+    music_director.stop();
 }
@@ -1,14 +1,30 @@
-//! Spawn the main map.
+//! Spawn the main map, and switch to neighboring maps at its edges.
 
 use bevy::prelude::*;
 
 use crate::{
+    AppSystems,
     asset_tracking::LoadResource,
-    game::tiled_map::{TiledMap, TiledMapBundle, TiledMapHandle},
+    game::{
+        facing::CompassDirection,
+        movement::MapEdgeReached,
+        tiled_map::{
+            CollisionTiles, LoadedMapPath, TiledMap, TiledMapBundle, TiledMapHandle,
+            process_loaded_maps,
+        },
+    },
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.load_resource::<MapAssets>();
+    app.add_observer(enter_neighboring_map);
+    app.add_systems(
+        Update,
+        arrive_at_map_edge
+            .run_if(resource_exists::<EdgeArrival>)
+            .after(process_loaded_maps)
+            .in_set(AppSystems::Update),
+    );
 }
 
 /// A system that spawns the main map.
@@ -39,3 +55,98 @@ impl FromWorld for MapAssets {
         }
     }
 }
+
+/// Switch to the map next to the current one when a character walks off its
+/// edge, and bring the character in on the opposite edge once it has loaded.
+/// Neighbors are set with `map_<direction>` string properties on maps with a
+/// `transition` edge, e.g. `map_north_east = "images/map/forest.tmx"`.
+fn enter_neighboring_map(
+    edge: On<MapEdgeReached>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    maps: Res<Assets<TiledMap>>,
+    collisions: Res<CollisionTiles>,
+    arrival: Option<Res<EdgeArrival>>,
+    mut map_query: Query<&mut TiledMapHandle>,
+) {
+    // The character keeps walking into the edge until the next map has loaded.
+    if arrival.is_some() {
+        return;
+    }
+    // Go the way of the edge that was crossed, which isn't necessarily the way
+    // the character was walking, e.g. when walking along a diagonal.
+    let coords = collisions.coords_at(edge.position);
+    let size = collisions.map_size.as_vec2();
+    let exit = Vec2::select(coords.cmplt(Vec2::ZERO), -Vec2::ONE, Vec2::ZERO)
+        + Vec2::select(coords.cmpge(size), Vec2::ONE, Vec2::ZERO);
+    let half_grid = collisions.grid_size * 0.5;
+    let world_exit = exit.x * half_grid + exit.y * Vec2::new(-half_grid.x, half_grid.y);
+    let Some(direction) = CompassDirection::from_vector(world_exit) else {
+        return;
+    };
+
+    let property = format!("map_{}", direction.name());
+    for mut map_handle in &mut map_query {
+        let Some(tiled_map) = maps.get(&map_handle.0) else {
+            continue;
+        };
+        let Some(tiled::PropertyValue::StringValue(path)) = tiled_map.map.properties.get(&property)
+        else {
+            continue;
+        };
+        map_handle.0 = asset_server.load(path.clone());
+        commands.insert_resource(EdgeArrival {
+            entity: edge.entity,
+            map: path.clone(),
+            coords,
+            map_size: collisions.map_size,
+        });
+    }
+}
+
+/// An entity that walked off the edge of a map, waiting for the map next to it
+/// to be loaded.
+#[derive(Resource, Debug)]
+struct EdgeArrival {
+    entity: Entity,
+    /// The asset path of the map being entered.
+    map: String,
+    /// Where the entity tried to move to, in the logical map coordinates of the
+    /// map it left.
+    coords: Vec2,
+    /// The size of the map it left.
+    map_size: UVec2,
+}
+
+/// Move the entity that walked off a map onto the opposite edge of the map next
+/// to it, once that has been loaded.
+fn arrive_at_map_edge(
+    mut commands: Commands,
+    arrival: Res<EdgeArrival>,
+    loaded_map: Res<LoadedMapPath>,
+    collisions: Res<CollisionTiles>,
+    mut transforms: Query<&mut Transform>,
+) {
+    if loaded_map.0.as_ref() != Some(&arrival.map) {
+        return;
+    }
+    commands.remove_resource::<EdgeArrival>();
+
+    // Step past the edge that was crossed, and stay level along the other axis.
+    let old_size = arrival.map_size.as_vec2();
+    let new_size = collisions.map_size.as_vec2();
+    let coords = Vec2::select(
+        arrival.coords.cmplt(Vec2::ZERO),
+        arrival.coords + new_size,
+        Vec2::select(
+            arrival.coords.cmpge(old_size),
+            arrival.coords - old_size,
+            arrival.coords,
+        ),
+    )
+    .clamp(Vec2::ZERO, (new_size - 0.01).max(Vec2::ZERO));
+    if let Ok(mut transform) = transforms.get_mut(arrival.entity) {
+        let position = collisions.world_at(coords);
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
@@ -0,0 +1,311 @@
+//! A minimap of the current map in the corner of the screen.
+//!
+//! The minimap is drawn from the map's collision and surface tiles, one color
+//! per explored tile, with the player and every [`MapMarker`] on top. It fills
+//! the screen while the map menu is open.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    image::ImageSampler,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{
+    AppSystems,
+    game::{
+        fog_of_war::ExploredTiles,
+        player::Player,
+        tiled_map::{CollisionTiles, LoadedMapPath, MapBounds, MapMarker, Surface, SurfaceTiles},
+    },
+    menus::Menu,
+    screens::Screen,
+    theme::palette::LABEL_TEXT,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_minimap);
+    app.add_systems(
+        Update,
+        (
+            draw_minimap.run_if(
+                resource_exists::<MinimapTexture>.and(
+                    resource_changed::<CollisionTiles>
+                        .or(resource_changed::<MapBounds>)
+                        .or(resource_changed::<ExploredTiles>)
+                        .or(resource_changed::<MinimapTexture>),
+                ),
+            ),
+            (add_minimap_icons, update_minimap_icons).chain(),
+            expand_minimap.run_if(state_changed::<Menu>),
+        )
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update),
+    );
+}
+
+/// How many minimap pixels each tile is wide.
+const PIXELS_PER_TILE: f32 = 4.0;
+
+/// The width of the minimap in the corner of the screen.
+const MINIMAP_WIDTH: f32 = 200.0;
+
+/// The size of the dots marking the player and points of interest.
+const ICON_SIZE: f32 = 8.0;
+
+const WALL_COLOR: Color = Color::srgb(0.22, 0.2, 0.24);
+const GROUND_COLOR: Color = Color::srgb(0.62, 0.54, 0.38);
+const GRASS_COLOR: Color = Color::srgb(0.36, 0.58, 0.29);
+const STONE_COLOR: Color = Color::srgb(0.55, 0.55, 0.58);
+const WATER_COLOR: Color = Color::srgb(0.25, 0.45, 0.78);
+const PLAYER_ICON_COLOR: Color = Color::WHITE;
+const MARKER_ICON_COLOR: Color = Color::srgb(0.9, 0.25, 0.2);
+
+/// The image the minimap is drawn to.
+#[derive(Resource, Debug)]
+struct MinimapTexture(Handle<Image>);
+
+/// The container positioning the minimap on the screen.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct Minimap;
+
+/// The node showing the [`MinimapTexture`].
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct MinimapImage;
+
+/// A dot on the minimap following an entity in the world.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct MinimapIcon {
+    target: Entity,
+}
+
+/// The name of a [`MapMarker`], only shown on the full-screen map.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct MinimapLabel;
+
+fn spawn_minimap(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    // Drawn once the map has been loaded, in `draw_minimap`.
+    let image = images.add(Image::default());
+    commands.insert_resource(MinimapTexture(image.clone()));
+
+    commands.spawn((
+        Name::new("Minimap"),
+        Minimap,
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            height: percent(100),
+            justify_content: JustifyContent::End,
+            align_items: AlignItems::Start,
+            padding: UiRect::all(px(16)),
+            ..default()
+        },
+        Pickable::IGNORE,
+        GlobalZIndex::default(),
+        DespawnOnExit(Screen::Gameplay),
+        children![(
+            Name::new("Minimap Image"),
+            MinimapImage,
+            ImageNode::new(image),
+            Node {
+                width: px(MINIMAP_WIDTH),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+        )],
+    ));
+}
+
+/// Draw the explored parts of the current map, coloring each tile by whether it
+/// is blocked and what its surface is.
+fn draw_minimap(
+    texture: Res<MinimapTexture>,
+    loaded_map: Res<LoadedMapPath>,
+    explored: Res<ExploredTiles>,
+    map_bounds: Res<MapBounds>,
+    collisions: Res<CollisionTiles>,
+    surfaces: Res<SurfaceTiles>,
+    mut images: ResMut<Assets<Image>>,
+    mut minimap_image: Single<&mut Node, With<MinimapImage>>,
+) {
+    let (Some(bounds), Some(map_path)) = (map_bounds.0, &loaded_map.0) else {
+        return;
+    };
+    let pixel_size = collisions.grid_size.x / PIXELS_PER_TILE;
+    if pixel_size <= 0.0 {
+        return;
+    }
+    let size = (bounds.size() / pixel_size).ceil().as_uvec2().max(UVec2::ONE);
+
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let world = Vec2::new(
+                bounds.min.x + (x as f32 + 0.5) * pixel_size,
+                bounds.max.y - (y as f32 + 0.5) * pixel_size,
+            );
+            let tile = collisions.tile_at(world);
+            if !collisions.in_map(tile) || !explored.is_explored(map_path, tile) {
+                continue;
+            }
+            let color = if collisions.blocked.contains(&tile) {
+                WALL_COLOR
+            } else {
+                surface_color(surfaces.surface_at(world))
+            };
+            // Can't fail, as the pixel is inside the image.
+            let _ = image.set_color_at(x, y, color);
+        }
+    }
+
+    if let Some(minimap) = images.get_mut(&texture.0) {
+        *minimap = image;
+    }
+    minimap_image.aspect_ratio = Some(size.x as f32 / size.y as f32);
+}
+
+fn surface_color(surface: Surface) -> Color {
+    match surface {
+        Surface::Default => GROUND_COLOR,
+        Surface::Grass => GRASS_COLOR,
+        Surface::Stone => STONE_COLOR,
+        Surface::Water => WATER_COLOR,
+    }
+}
+
+fn add_minimap_icons(
+    mut commands: Commands,
+    menu: Res<State<Menu>>,
+    minimap_image: Single<Entity, With<MinimapImage>>,
+    players: Query<Entity, Added<Player>>,
+    markers: Query<(Entity, &MapMarker), Added<MapMarker>>,
+) {
+    let minimap_image = *minimap_image;
+    for player in &players {
+        commands.spawn((
+            Name::new("Player Icon"),
+            minimap_icon(player, PLAYER_ICON_COLOR),
+            ChildOf(minimap_image),
+        ));
+    }
+
+    let label_visibility = if *menu.get() == Menu::Map {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for (marker, map_marker) in &markers {
+        commands.spawn((
+            Name::new(format!("Marker Icon {}", map_marker.label)),
+            minimap_icon(marker, MARKER_ICON_COLOR),
+            ChildOf(minimap_image),
+            children![(
+                Name::new("Marker Label"),
+                MinimapLabel,
+                Text(map_marker.label.clone()),
+                TextFont::from_font_size(16.0),
+                TextColor(LABEL_TEXT),
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: px(ICON_SIZE + 4.0),
+                    top: px(-ICON_SIZE),
+                    ..default()
+                },
+                label_visibility,
+            )],
+        ));
+    }
+}
+
+fn minimap_icon(target: Entity, color: Color) -> impl Bundle {
+    (
+        MinimapIcon { target },
+        Node {
+            position_type: PositionType::Absolute,
+            width: px(ICON_SIZE),
+            height: px(ICON_SIZE),
+            // Center the dot on its position.
+            margin: UiRect {
+                left: px(-ICON_SIZE / 2.0),
+                top: px(-ICON_SIZE / 2.0),
+                ..default()
+            },
+            ..default()
+        },
+        BackgroundColor(color),
+        BorderRadius::MAX,
+    )
+}
+
+/// Move the icons to where their targets are on the map, and remove the icons
+/// of despawned targets.
+fn update_minimap_icons(
+    mut commands: Commands,
+    map_bounds: Res<MapBounds>,
+    targets: Query<&GlobalTransform>,
+    mut icons: Query<(Entity, &MinimapIcon, &mut Node)>,
+) {
+    let Some(bounds) = map_bounds.0 else {
+        return;
+    };
+    for (icon, MinimapIcon { target }, mut node) in &mut icons {
+        let Ok(transform) = targets.get(*target) else {
+            commands.entity(icon).despawn();
+            continue;
+        };
+        let position = (transform.translation().xy() - bounds.min) / bounds.size();
+        node.left = percent(position.x * 100.0);
+        node.top = percent((1.0 - position.y) * 100.0);
+    }
+}
+
+/// Fill the screen with the minimap while the map menu is open, showing the
+/// names of the markers, and put it back in the corner afterwards.
+fn expand_minimap(
+    menu: Res<State<Menu>>,
+    minimap: Single<(&mut Node, &mut GlobalZIndex), With<Minimap>>,
+    mut minimap_image: Single<&mut Node, (With<MinimapImage>, Without<Minimap>)>,
+    mut labels: Query<&mut Visibility, With<MinimapLabel>>,
+) {
+    let expanded = *menu.get() == Menu::Map;
+    let (mut container, mut z_index) = minimap.into_inner();
+    if expanded {
+        container.justify_content = JustifyContent::Center;
+        container.align_items = AlignItems::Center;
+        // Above the pause overlay.
+        *z_index = GlobalZIndex(2);
+        minimap_image.width = Val::Auto;
+        minimap_image.height = percent(70);
+        minimap_image.max_width = percent(90);
+    } else {
+        container.justify_content = JustifyContent::End;
+        container.align_items = AlignItems::Start;
+        *z_index = GlobalZIndex::default();
+        minimap_image.width = px(MINIMAP_WIDTH);
+        minimap_image.height = Val::Auto;
+        minimap_image.max_width = Val::Auto;
+    }
+
+    for mut visibility in &mut labels {
+        *visibility = if expanded {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
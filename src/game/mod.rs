@@ -8,20 +8,60 @@ use bevy::prelude::*;
 use crate::game::tiled_map::TiledMap;
 
 mod animation;
+pub mod camera;
+pub mod camera_shake;
+pub mod combat;
+pub mod dialogue;
+pub mod experience;
+pub mod facing;
+pub mod flags;
+pub mod fog_of_war;
+pub mod footsteps;
+pub mod interaction;
+pub mod inventory;
+pub mod items;
 pub mod level;
 pub mod map;
+mod minimap;
 mod movement;
+pub mod pixel_perfect;
 pub mod player;
+pub mod props;
+pub mod quests;
+pub mod save_game;
+pub mod sprite_animation;
+pub mod stats;
 pub mod tiled_map;
 
 pub(super) fn plugin(app: &mut App) {
     app.init_asset::<TiledMap>();
     app.add_plugins((
         animation::plugin,
+        camera::plugin,
+        camera_shake::plugin,
+        facing::plugin,
+        fog_of_war::plugin,
+        footsteps::plugin,
         level::plugin,
         movement::plugin,
+        pixel_perfect::plugin,
         player::plugin,
         map::plugin,
+        minimap::plugin,
+        sprite_animation::plugin,
         tiled_map::plugin,
     ));
+    app.add_plugins((
+        combat::plugin,
+        dialogue::plugin,
+        experience::plugin,
+        flags::plugin,
+        interaction::plugin,
+        inventory::plugin,
+        items::plugin,
+        props::plugin,
+        quests::plugin,
+        save_game::plugin,
+        stats::plugin,
+    ));
 }
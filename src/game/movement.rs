@@ -7,21 +7,24 @@
 //!   This is done in the `player` module, as it is specific to the player
 //!   character.
 //! - Apply movement based on [`MovementController`] intent and maximum speed.
-//! - Wrap the character within the window.
+//! - Keep characters with a [`MapBoundary`] inside the current map, as set by
+//!   its [`MapEdgeMode`].
 //!
 //! Note that the implementation used here is limited for demonstration
 //! purposes. If you want to move the player in a smoother way,
 //! consider using a [fixed timestep](https://github.com/bevyengine/bevy/blob/main/examples/movement/physics_in_fixed_timestep.rs).
 
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::prelude::*;
 
-use crate::{AppSystems, PausableSystems, game::tiled_map::CollisionTiles};
+use crate::{
+    AppSystems, PausableSystems,
+    game::tiled_map::{CollisionTiles, MapEdgeMode},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(
         Update,
-        (apply_movement, apply_screen_wrap)
-            .chain()
+        apply_movement
             .in_set(AppSystems::Update)
             .in_set(PausableSystems),
     );
@@ -39,6 +42,10 @@ pub struct MovementController {
     /// Maximum speed in world units per second.
     /// 1 world unit = 1 pixel when using the default 2D camera and no physics engine.
     pub max_speed: f32,
+
+    /// Whether the character is kept in place regardless of its intent, e.g.
+    /// during an attack.
+    pub locked: bool,
 }
 
 impl Default for MovementController {
@@ -47,29 +54,62 @@ impl Default for MovementController {
             intent: Vec2::ZERO,
             // 400 pixels per second is a nice default, but we can still vary this per character.
             max_speed: 400.0,
+            locked: false,
         }
     }
 }
 
+/// Keeps a character inside the current map. What happens at the edge of the
+/// map depends on the map's [`MapEdgeMode`].
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct MapBoundary;
+
+/// Triggered on a [`MapBoundary`] entity every frame it tries to move past the
+/// edge of a map with [`MapEdgeMode::Transition`].
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct MapEdgeReached {
+    pub entity: Entity,
+    /// Where the entity tried to move to, just past the edge.
+    pub position: Vec2,
+}
+
 fn apply_movement(
+    mut commands: Commands,
     time: Res<Time>,
     collisions: Res<CollisionTiles>,
-    mut movement_query: Query<(&MovementController, &mut Transform)>,
+    edge_mode: Res<MapEdgeMode>,
+    mut movement_query: Query<(Entity, &MovementController, &mut Transform, Has<MapBoundary>)>,
 ) {
-    for (controller, mut transform) in &mut movement_query {
+    for (entity, controller, mut transform, has_boundary) in &mut movement_query {
+        if controller.locked {
+            continue;
+        }
         let velocity = controller.max_speed * controller.intent;
-
-        if velocity.length_squared() == 0.0 || collisions.blocked.is_empty() {
-            transform.translation += velocity.extend(0.0) * time.delta_secs();
+        if velocity.length_squared() == 0.0 {
             continue;
         }
 
         let current = transform.translation.xy();
-        let target = current + velocity * time.delta_secs();
+        let mut target = current + velocity * time.delta_secs();
+        let target_tile = collisions.tile_at(target);
+
+        if has_boundary && !collisions.in_map(target_tile) {
+            match *edge_mode {
+                MapEdgeMode::Contain => continue,
+                MapEdgeMode::Transition => {
+                    commands.trigger(MapEdgeReached {
+                        entity,
+                        position: target,
+                    });
+                    continue;
+                }
+                MapEdgeMode::Wrap => target = collisions.wrap(target),
+            }
+        }
 
         // If target tile is blocked, prevent movement this frame
-        let target_tile = world_to_iso_tile(target, &collisions);
-        if collisions.blocked.contains(&target_tile) {
+        if collisions.blocked.contains(&collisions.tile_at(target)) {
             continue;
         }
 
@@ -77,43 +117,53 @@ fn apply_movement(
     }
 }
 
-fn world_to_iso_tile(world: Vec2, collisions: &CollisionTiles) -> IVec2 {
-    let half_w = collisions.grid_size.x * 0.5;
-    let half_h = collisions.grid_size.y * 0.5;
+/// Map a screen-space intent onto the axes of an isometric grid with the given
+/// tile size, so that each of the eight input directions walks along a row,
+/// column or diagonal of tiles. The result is normalized.
+///
+/// Falls back to the screen axes if no grid has been loaded yet.
+pub fn isometric_intent(intent: Vec2, grid_size: Vec2) -> Vec2 {
+    if grid_size == Vec2::ZERO {
+        return intent.normalize_or_zero();
+    }
+
+    // Screen directions of the tile X axis (up) and the negative tile Y axis (right).
+    let up = grid_size.normalize();
+    let right = Vec2::new(grid_size.x, -grid_size.y).normalize();
 
-    // Center of the map in tile coordinates (due to TilemapAnchor::Center)
-    let center_x = (collisions.map_size.x as f32 - 1.0) * 0.5;
-    let center_y = (collisions.map_size.y as f32 - 1.0) * 0.5;
+    (intent.x * right + intent.y * up).normalize_or_zero()
+}
 
-    // Undo tilemap transform (offset applied at spawn)
-    let local = world - collisions.layer_offset;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Convert to skewed isometric space
-    let sx = local.x / half_w;
-    let sy = local.y / half_h;
+    const GRID_SIZE: Vec2 = Vec2::new(64.0, 32.0);
 
-    // Inverse of:
-    // world.x = (x - y - (center_x - center_y)) * half_w + offset_x
-    // world.y = (x + y - (center_x + center_y)) * half_h - offset_y
-    let tx = (sy + sx) * 0.5 + center_x;
-    let ty = (sy - sx) * 0.5 + center_y;
+    #[test]
+    fn straight_input_follows_the_tile_axes() {
+        let up = isometric_intent(Vec2::Y, GRID_SIZE);
+        assert!(up.abs_diff_eq(GRID_SIZE.normalize(), 1e-6));
+        let right = isometric_intent(Vec2::X, GRID_SIZE);
+        assert!(right.abs_diff_eq(Vec2::new(64.0, -32.0).normalize(), 1e-6));
+    }
 
-    IVec2::new(tx.floor() as i32, ty.floor() as i32)
-}
+    #[test]
+    fn diagonal_input_follows_the_tile_diagonals() {
+        let up_right = isometric_intent(Vec2::ONE, GRID_SIZE);
+        assert!(up_right.abs_diff_eq(Vec2::X, 1e-6));
+        let down_right = isometric_intent(Vec2::new(1.0, -1.0), GRID_SIZE);
+        assert!(down_right.abs_diff_eq(Vec2::NEG_Y, 1e-6));
+    }
 
-#[derive(Component, Reflect)]
-#[reflect(Component)]
-pub struct ScreenWrap;
+    #[test]
+    fn no_input_stays_still() {
+        assert_eq!(isometric_intent(Vec2::ZERO, GRID_SIZE), Vec2::ZERO);
+    }
 
-fn apply_screen_wrap(
-    window: Single<&Window, With<PrimaryWindow>>,
-    mut wrap_query: Query<&mut Transform, With<ScreenWrap>>,
-) {
-    let size = window.size() + 256.0;
-    let half_size = size / 2.0;
-    for mut transform in &mut wrap_query {
-        let position = transform.translation.xy();
-        let wrapped = (position + half_size).rem_euclid(size) - half_size;
-        transform.translation = wrapped.extend(transform.translation.z);
+    #[test]
+    fn without_a_grid_input_is_only_normalized() {
+        let intent = isometric_intent(Vec2::new(3.0, 4.0), Vec2::ZERO);
+        assert!(intent.abs_diff_eq(Vec2::new(0.6, 0.8), 1e-6));
     }
 }
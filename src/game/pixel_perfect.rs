@@ -0,0 +1,188 @@
+//! Optional pixel-perfect rendering.
+//!
+//! While [`PixelPerfect`] is on, the world camera renders to a low-resolution
+//! canvas, one texel per world unit, and an upscale camera draws the canvas to
+//! the window at a whole-number scale: the rounded [`CameraZoom`]. The world
+//! camera is snapped to whole texels, and the canvas is shifted by what was
+//! left over in screen pixels, so camera movement stays smooth.
+
+use bevy::{
+    camera::{RenderTarget, visibility::RenderLayers},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureFormat},
+    window::PrimaryWindow,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    camera::CameraZoom,
+    camera_shake::{apply_camera_shake, remove_camera_shake},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PixelPerfect>();
+
+    app.add_systems(
+        PostUpdate,
+        (
+            (
+                toggle_pixel_perfect.run_if(resource_changed::<PixelPerfect>),
+                unsnap_world_camera.run_if(resource_exists::<PixelCanvas>),
+            )
+                .chain()
+                .before(remove_camera_shake),
+            (resize_canvas, snap_world_camera)
+                .chain()
+                .run_if(resource_exists::<PixelCanvas>)
+                .after(apply_camera_shake)
+                .before(TransformSystems::Propagate),
+        ),
+    );
+}
+
+/// The render layer of the canvas, which only the upscale camera sees.
+const CANVAS_LAYER: usize = 1;
+
+/// A graphics setting that renders the world at its native resolution and
+/// scales it up by whole numbers, so that pixel art doesn't shimmer.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct PixelPerfect(pub bool);
+
+/// The low-resolution image the world is rendered to while [`PixelPerfect`] is on.
+#[derive(Resource, Debug)]
+struct PixelCanvas {
+    image: Handle<Image>,
+    /// Where the world camera was before it was snapped to whole texels, to be
+    /// restored before the camera moves again.
+    unsnapped: Option<Vec2>,
+}
+
+/// The camera that draws the [`PixelCanvas`] to the window.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct UpscaleCamera;
+
+/// The sprite showing the [`PixelCanvas`].
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct CanvasSprite;
+
+fn toggle_pixel_perfect(
+    mut commands: Commands,
+    pixel_perfect: Res<PixelPerfect>,
+    canvas: Option<Res<PixelCanvas>>,
+    mut images: ResMut<Assets<Image>>,
+    mut zoom: ResMut<CameraZoom>,
+    world_camera: Single<(&mut Camera, &mut Transform), (With<Camera2d>, Without<UpscaleCamera>)>,
+    upscale_entities: Query<Entity, Or<(With<UpscaleCamera>, With<CanvasSprite>)>>,
+) {
+    let (mut camera, mut transform) = world_camera.into_inner();
+    match (pixel_perfect.0, canvas) {
+        (true, None) => {
+            // The canvas is sized to the window in `resize_canvas`.
+            let image = images.add(Image::new_target_texture(
+                2,
+                2,
+                TextureFormat::bevy_default(),
+            ));
+            camera.target = RenderTarget::Image(image.clone().into());
+            commands.spawn((
+                Name::new("Upscale Camera"),
+                UpscaleCamera,
+                Camera2d,
+                Camera {
+                    // Render after the world camera.
+                    order: 1,
+                    ..default()
+                },
+                RenderLayers::layer(CANVAS_LAYER),
+            ));
+            commands.spawn((
+                Name::new("Pixel Canvas"),
+                CanvasSprite,
+                Sprite::from_image(image.clone()),
+                RenderLayers::layer(CANVAS_LAYER),
+            ));
+            commands.insert_resource(PixelCanvas {
+                image,
+                unsnapped: None,
+            });
+
+            let whole_zoom = zoom.0.round().max(1.0);
+            if zoom.0 != whole_zoom {
+                zoom.0 = whole_zoom;
+            }
+        }
+        (false, Some(canvas)) => {
+            if let Some(unsnapped) = canvas.unsnapped {
+                transform.translation = unsnapped.extend(transform.translation.z);
+            }
+            camera.target = RenderTarget::default();
+            for entity in &upscale_entities {
+                commands.entity(entity).despawn();
+            }
+            commands.remove_resource::<PixelCanvas>();
+        }
+        _ => {}
+    }
+}
+
+/// Undo last frame's snapping, so that the camera moves on from where it would
+/// be without it.
+fn unsnap_world_camera(
+    mut canvas: ResMut<PixelCanvas>,
+    mut world_camera: Single<&mut Transform, (With<Camera2d>, Without<UpscaleCamera>)>,
+) {
+    if let Some(unsnapped) = canvas.unsnapped.take() {
+        world_camera.translation = unsnapped.extend(world_camera.translation.z);
+    }
+}
+
+/// How many physical pixels of the window each texel of the canvas covers.
+fn canvas_scale(zoom: &CameraZoom) -> f32 {
+    zoom.0.round().max(1.0)
+}
+
+/// Keep the canvas large enough to cover the window at the current scale, with
+/// a texel to spare on each side for the sub-texel shift.
+fn resize_canvas(
+    canvas: Res<PixelCanvas>,
+    zoom: Res<CameraZoom>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let texels = (window.physical_size().as_vec2() / canvas_scale(&zoom)).ceil().as_uvec2();
+    // Even sizes keep the center of the view on a texel corner.
+    let size = (texels + 2).map(|length| length.next_multiple_of(2));
+    if images
+        .get(&canvas.image)
+        .is_some_and(|image| image.size() != size)
+        && let Some(image) = images.get_mut(&canvas.image)
+    {
+        image.resize(Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        });
+    }
+}
+
+fn snap_world_camera(
+    mut canvas: ResMut<PixelCanvas>,
+    zoom: Res<CameraZoom>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut world_camera: Single<&mut Transform, (With<Camera2d>, Without<UpscaleCamera>)>,
+    mut canvas_sprite: Single<&mut Transform, (With<CanvasSprite>, Without<Camera2d>)>,
+) {
+    let position = world_camera.translation.xy();
+    let snapped = position.round();
+    canvas.unsnapped = Some(position);
+    world_camera.translation = snapped.extend(world_camera.translation.z);
+
+    // Scale in logical pixels, so that each texel covers whole physical pixels.
+    let texel_size = canvas_scale(&zoom) / window.scale_factor();
+    canvas_sprite.scale = Vec3::new(texel_size, texel_size, 1.0);
+    let shift = (snapped - position) * texel_size;
+    canvas_sprite.translation = shift.extend(0.0);
+}
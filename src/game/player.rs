@@ -1,62 +1,68 @@
 //! Player-specific behavior.
 
-use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
-use bevy::{
-    image::{ImageLoaderSettings, ImageSampler},
-    prelude::*,
-};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     AppSystems, PausableSystems,
     asset_tracking::LoadResource,
     game::{
-        animation::PlayerAnimation,
-        movement::{MovementController, ScreenWrap},
+        animation::{PlayOneShot, PlayerAnimation},
+        camera::CameraTarget,
+        combat::{Defeated, Health},
+        experience::Experience,
+        facing::Facing,
+        fog_of_war::Vision,
+        footsteps::Footsteps,
+        inventory::Inventory,
+        movement::{MapBoundary, MovementController, isometric_intent},
+        sprite_animation::{SpriteAnimations, SpriteAnimator},
+        stats::{StatValues, Stats},
+        tiled_map::CollisionTiles,
     },
+    input::{ActionState, InputAction, left_stick, right_stick},
+    screens::Screen,
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.load_resource::<PlayerAssets>();
+    app.init_resource::<MovementInputMode>();
 
-    // Record directional input as movement controls.
+    // Record directional input as movement controls, and attack input.
     app.add_systems(
         Update,
-        (record_player_directional_input)
+        (record_player_directional_input, record_player_attack_input)
             .in_set(AppSystems::RecordInput)
             .in_set(PausableSystems),
     );
 
-    // Make the main 2D camera follow the player.
+    // Go back to the title screen a while after the player is defeated.
+    app.add_observer(start_game_over);
     app.add_systems(
         Update,
-        follow_player_camera
+        end_game_over
+            .run_if(resource_exists::<GameOver>)
             .in_set(AppSystems::Update)
             .in_set(PausableSystems),
     );
+    app.add_systems(OnExit(Screen::Gameplay), clear_game_over);
 }
 
-/// The player character.
-pub fn player(
-    max_speed: f32,
-    player_assets: &PlayerAssets,
-    texture_atlas_layouts: &mut Assets<TextureAtlasLayout>,
-) -> impl Bundle {
-    // A texture atlas is a way to split a single image into a grid of related images.
-    // You can learn more in this example: https://github.com/bevyengine/bevy/blob/latest/examples/2d/texture_atlas.rs
-    let layout = TextureAtlasLayout::from_grid(UVec2::new(96, 80), 8, 1, None, None);
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
-    let player_animation = PlayerAnimation::new();
+const PLAYER_MAX_HEALTH: f32 = 100.0;
+
+/// How long the game goes on after the player is defeated, to play the `death`
+/// clip, before going back to the title screen.
+const GAME_OVER_DELAY: Duration = Duration::from_millis(2500);
 
+/// The player character.
+pub fn player(max_speed: f32, player_assets: &PlayerAssets) -> impl Bundle {
     (
         Name::new("Player"),
         Player,
-        Sprite::from_atlas_image(
-            player_assets.spritesheets[2].clone(),
-            TextureAtlas {
-                layout: texture_atlas_layout,
-                index: player_animation.get_atlas_index(),
-            },
-        ),
+        CameraTarget,
+        SpriteAnimator::new(player_assets.animations.clone(), "idle_south"),
         Transform {
             translation: Vec3::new(0., 16., 3.),
             scale: Vec2::splat(1.0).extend(1.0),
@@ -66,183 +72,169 @@ pub fn player(
             max_speed,
             ..default()
         },
-        ScreenWrap,
-        player_animation,
+        Stats::new(StatValues {
+            strength: 10.0,
+            defense: 5.0,
+            speed: max_speed,
+            max_health: PLAYER_MAX_HEALTH,
+        }),
+        Health::new(PLAYER_MAX_HEALTH),
+        Experience::default(),
+        Inventory::default(),
+        MapBoundary,
+        Footsteps::default(),
+        Facing::default(),
+        Vision::default(),
+        PlayerAnimation::new(),
     )
 }
 
+/// Marks the player character.
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
 #[reflect(Component)]
-struct Player;
+pub struct Player;
+
+/// How directional input is mapped onto movement.
+#[derive(
+    Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Resource)]
+pub enum MovementInputMode {
+    /// Directions follow the screen axes (up is up on the screen).
+    #[default]
+    Screen,
+    /// Directions follow the isometric grid axes, so every direction walks
+    /// along a row, column or diagonal of tiles.
+    Isometric,
+}
+
+impl MovementInputMode {
+    /// Switch to the other input mode.
+    pub fn toggle(&mut self) {
+        *self = match self {
+            Self::Screen => Self::Isometric,
+            Self::Isometric => Self::Screen,
+        };
+    }
+
+    /// A human-readable name for the settings menu.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Screen => "Screen",
+            Self::Isometric => "Isometric",
+        }
+    }
+}
 
 fn record_player_directional_input(
-    input: Res<ButtonInput<KeyCode>>,
-    mut controller_query: Query<&mut MovementController, With<Player>>,
+    actions: Res<ActionState>,
+    gamepads: Query<&Gamepad>,
+    input_mode: Res<MovementInputMode>,
+    collisions: Res<CollisionTiles>,
+    mut controller_query: Query<(&mut MovementController, &mut Facing), With<Player>>,
 ) {
     // Collect directional input.
     let mut intent = Vec2::ZERO;
-    if input.pressed(KeyCode::KeyW) || input.pressed(KeyCode::ArrowUp) {
+    if actions.pressed(InputAction::MoveUp) {
         intent.y += 1.0;
     }
-    if input.pressed(KeyCode::KeyS) || input.pressed(KeyCode::ArrowDown) {
+    if actions.pressed(InputAction::MoveDown) {
         intent.y -= 1.0;
     }
-    if input.pressed(KeyCode::KeyA) || input.pressed(KeyCode::ArrowLeft) {
+    if actions.pressed(InputAction::MoveLeft) {
         intent.x -= 1.0;
     }
-    if input.pressed(KeyCode::KeyD) || input.pressed(KeyCode::ArrowRight) {
+    if actions.pressed(InputAction::MoveRight) {
         intent.x += 1.0;
     }
 
     // Normalize intent so that diagonal movement is the same speed as horizontal / vertical.
     // This should be omitted if the input comes from an analog stick instead.
-    let intent = intent.normalize_or_zero();
+    let mut intent = match *input_mode {
+        MovementInputMode::Screen => intent.normalize_or_zero(),
+        MovementInputMode::Isometric => isometric_intent(intent, collisions.grid_size),
+    };
+
+    // Analog stick input takes precedence and keeps its magnitude, so a partial
+    // tilt walks slower than a full one.
+    let stick = left_stick(&gamepads);
+    if stick != Vec2::ZERO {
+        intent = match *input_mode {
+            MovementInputMode::Screen => stick,
+            MovementInputMode::Isometric => {
+                isometric_intent(stick, collisions.grid_size) * stick.length()
+            }
+        };
+    }
+
+    // The right stick aims, so the player can face one way while walking another.
+    let aim = right_stick(&gamepads);
 
     // Apply movement intent to controllers.
-    for mut controller in &mut controller_query {
+    for (mut controller, mut facing) in &mut controller_query {
         controller.intent = intent;
+        facing.aim = aim;
     }
 }
 
-fn follow_player_camera(
-    player_transform: Single<&Transform, With<Player>>,
-    mut camera_query: Query<&mut Transform, (With<Camera2d>, Without<Player>)>,
-    mut mouse_wheel: MessageReader<MouseWheel>,
+fn record_player_attack_input(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    player_query: Query<Entity, With<Player>>,
 ) {
-    // Accumulate scroll input this frame
-    let mut scroll = 0.0f32;
-    for ev in mouse_wheel.read() {
-        let step = match ev.unit {
-            MouseScrollUnit::Line => 0.1,
-            MouseScrollUnit::Pixel => 0.001,
-        };
-        scroll += ev.y as f32 * step;
+    if !actions.just_pressed(InputAction::Attack) {
+        return;
     }
 
-    for mut cam_transform in &mut camera_query {
-        // Follow player position
-        cam_transform.translation.x = player_transform.translation.x;
-        cam_transform.translation.y = player_transform.translation.y;
-
-        // Apply zoom via camera transform scaling (scroll up -> zoom in)
-        if scroll != 0.0 {
-            let current = cam_transform.scale.x.max(0.0001);
-            let target = (current * (1.0 - scroll)).clamp(0.25, 3.0);
-            cam_transform.scale.x = target;
-            cam_transform.scale.y = target;
-            // Keep Z scale unchanged
-        }
+    for entity in &player_query {
+        commands.trigger(PlayOneShot {
+            entity,
+            animation: "attack".to_string(),
+        });
+    }
+}
+
+/// The time left until going back to the title screen, after the player has
+/// been defeated.
+#[derive(Resource, Debug)]
+struct GameOver(Timer);
+
+fn start_game_over(
+    defeated: On<Defeated>,
+    mut commands: Commands,
+    player_query: Query<(), With<Player>>,
+) {
+    if player_query.contains(defeated.entity) {
+        commands.insert_resource(GameOver(Timer::new(GAME_OVER_DELAY, TimerMode::Once)));
+    }
+}
+
+fn end_game_over(
+    time: Res<Time>,
+    mut game_over: ResMut<GameOver>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    if game_over.0.tick(time.delta()).just_finished() {
+        next_screen.set(Screen::Title);
     }
 }
 
+fn clear_game_over(mut commands: Commands) {
+    commands.remove_resource::<GameOver>();
+}
+
 #[derive(Resource, Asset, Clone, Reflect)]
 #[reflect(Resource)]
 pub struct PlayerAssets {
     #[dependency]
-    pub spritesheets: Vec<Handle<Image>>,
-    #[dependency]
-    pub sounds: Vec<Handle<AudioSource>>,
+    pub animations: Handle<SpriteAnimations>,
 }
 
 impl FromWorld for PlayerAssets {
     fn from_world(world: &mut World) -> Self {
         let assets = world.resource::<AssetServer>();
         Self {
-            spritesheets: vec![
-                // IDLE
-                assets.load_with_settings(
-                    "images/player/idle/idle_east.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        // Use `nearest` image sampling to preserve pixel art style.
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-                assets.load_with_settings(
-                    "images/player/idle/idle_north.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        // Use `nearest` image sampling to preserve pixel art style.
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-                assets.load_with_settings(
-                    "images/player/idle/idle_south.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        // Use `nearest` image sampling to preserve pixel art style.
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-                assets.load_with_settings(
-                    "images/player/idle/idle_west.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        // Use `nearest` image sampling to preserve pixel art style.
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-                // RUN
-                assets.load_with_settings(
-                    "images/player/run/run_east.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        // Use `nearest` image sampling to preserve pixel art style.
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-                assets.load_with_settings(
-                    "images/player/run/run_north.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        // Use `nearest` image sampling to preserve pixel art style.
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-                assets.load_with_settings(
-                    "images/player/run/run_south.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        // Use `nearest` image sampling to preserve pixel art style.
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-                assets.load_with_settings(
-                    "images/player/run/run_west.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        // Use `nearest` image sampling to preserve pixel art style.
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-                // ATTACK
-                assets.load_with_settings(
-                    "images/player/attack/attack_east.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        // Use `nearest` image sampling to preserve pixel art style.
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-                assets.load_with_settings(
-                    "images/player/attack/attack_north.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        // Use `nearest` image sampling to preserve pixel art style.
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-                assets.load_with_settings(
-                    "images/player/attack/attack_south.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        // Use `nearest` image sampling to preserve pixel art style.
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-                assets.load_with_settings(
-                    "images/player/attack/attack_west.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        // Use `nearest` image sampling to preserve pixel art style.
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-            ],
-            sounds: vec![
-                assets.load("audio/sound_effects/step1.ogg"),
-                assets.load("audio/sound_effects/step2.ogg"),
-                assets.load("audio/sound_effects/step3.ogg"),
-                assets.load("audio/sound_effects/step4.ogg"),
-            ],
+            animations: assets.load("animations/player.anim.ron"),
         }
     }
 }
@@ -0,0 +1,230 @@
+//! Props placed in maps that the player can interact with: chests holding
+//! items, doors leading to [`MapMarker`]s, and signs to read.
+
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems,
+    game::{
+        dialogue::{DialogueScript, StartDialogue},
+        flags::GameFlags,
+        interaction::{Interact, Interactable},
+        inventory::{GiveItem, ItemStack},
+        tiled_map::{LoadedMapPath, MapMarker, TiledMapHandle, process_loaded_maps},
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(open_chest);
+    app.add_observer(enter_door);
+    app.add_observer(read_sign);
+    app.add_systems(
+        Update,
+        (
+            empty_opened_chests,
+            arrive_through_door
+                .run_if(resource_exists::<DoorArrival>)
+                .after(process_loaded_maps),
+        )
+            .in_set(AppSystems::Update),
+    );
+}
+
+/// How close the player has to be to a prop to interact with it.
+const PROP_RADIUS: f32 = 20.0;
+
+const CHEST_SIZE: Vec2 = Vec2::new(14.0, 10.0);
+const CHEST_COLOR: Color = Color::srgb(0.6, 0.4, 0.15);
+const OPENED_CHEST_COLOR: Color = Color::srgb(0.35, 0.25, 0.12);
+
+const DOOR_SIZE: Vec2 = Vec2::new(12.0, 18.0);
+const DOOR_COLOR: Color = Color::srgb(0.3, 0.2, 0.1);
+
+const SIGN_SIZE: Vec2 = Vec2::new(12.0, 10.0);
+const SIGN_COLOR: Color = Color::srgb(0.8, 0.7, 0.5);
+
+/// A chest holding items for whoever opens it. A flag is set when it is opened,
+/// so that it stays empty.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Chest {
+    pub stack: ItemStack,
+    /// The flag that is set once the chest has been opened.
+    pub opened_flag: String,
+}
+
+/// A chest at `position`.
+pub fn chest(chest: Chest, position: Vec2) -> impl Bundle {
+    (
+        Name::new(format!("Chest {}", chest.stack.item)),
+        chest,
+        Interactable::new(PROP_RADIUS, "Open"),
+        Sprite::from_color(CHEST_COLOR, CHEST_SIZE),
+        // Just below characters.
+        Transform::from_translation(position.extend(2.0)),
+    )
+}
+
+/// A door leading to the [`MapMarker`] with the label `marker`.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Door {
+    pub marker: String,
+    /// The asset path of the map the marker is on, or `None` for the current map.
+    pub map: Option<String>,
+}
+
+/// A door at `position`.
+pub fn door(door: Door, position: Vec2) -> impl Bundle {
+    (
+        Name::new(format!("Door to {}", door.marker)),
+        door,
+        Interactable::new(PROP_RADIUS, "Enter"),
+        Sprite::from_color(DOOR_COLOR, DOOR_SIZE),
+        // Just below characters.
+        Transform::from_translation(position.extend(2.0)),
+    )
+}
+
+/// A sign whose text is shown in the dialogue box, a line at a time.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Sign {
+    pub name: String,
+    pub text: String,
+}
+
+/// A sign at `position`.
+pub fn sign(sign: Sign, position: Vec2) -> impl Bundle {
+    (
+        Name::new(format!("Sign {}", sign.name)),
+        sign,
+        Interactable::new(PROP_RADIUS, "Read"),
+        Sprite::from_color(SIGN_COLOR, SIGN_SIZE),
+        // Just below characters.
+        Transform::from_translation(position.extend(2.0)),
+    )
+}
+
+/// An entity that went through a [`Door`], waiting for the map it leads to to
+/// be loaded.
+#[derive(Resource, Debug)]
+struct DoorArrival {
+    entity: Entity,
+    marker: String,
+    map: Option<String>,
+}
+
+fn open_chest(
+    interact: On<Interact>,
+    mut commands: Commands,
+    chest_query: Query<&Chest>,
+    mut flags: ResMut<GameFlags>,
+) {
+    let Ok(chest) = chest_query.get(interact.entity) else {
+        return;
+    };
+    if flags.is_set(&chest.opened_flag) {
+        return;
+    }
+    flags.set(chest.opened_flag.clone());
+    commands.trigger(GiveItem {
+        entity: interact.by,
+        stack: chest.stack.clone(),
+    });
+}
+
+/// Stop offering to open chests that have been opened, including ones opened
+/// before the game was saved.
+fn empty_opened_chests(
+    mut commands: Commands,
+    flags: Res<GameFlags>,
+    mut chest_query: Query<(Entity, &Chest, &mut Sprite), With<Interactable>>,
+) {
+    for (entity, chest, mut sprite) in &mut chest_query {
+        if flags.is_set(&chest.opened_flag) {
+            sprite.color = OPENED_CHEST_COLOR;
+            commands.entity(entity).remove::<Interactable>();
+        }
+    }
+}
+
+fn enter_door(
+    interact: On<Interact>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    loaded_map: Res<LoadedMapPath>,
+    door_query: Query<&Door>,
+    mut map_query: Query<&mut TiledMapHandle>,
+) {
+    let Ok(door) = door_query.get(interact.entity) else {
+        return;
+    };
+    if let Some(map) = &door.map
+        && loaded_map.0.as_ref() != Some(map)
+    {
+        for mut map_handle in &mut map_query {
+            map_handle.0 = asset_server.load(map.clone());
+        }
+    }
+    commands.insert_resource(DoorArrival {
+        entity: interact.by,
+        marker: door.marker.clone(),
+        map: door.map.clone(),
+    });
+}
+
+/// Move the entity that went through a door to the marker it leads to, once
+/// the map the marker is on has been loaded.
+fn arrive_through_door(
+    mut commands: Commands,
+    arrival: Res<DoorArrival>,
+    loaded_map: Res<LoadedMapPath>,
+    marker_query: Query<(&MapMarker, &Transform)>,
+    mut transforms: Query<&mut Transform, Without<MapMarker>>,
+) {
+    if arrival
+        .map
+        .as_ref()
+        .is_some_and(|map| loaded_map.0.as_ref() != Some(map))
+    {
+        return;
+    }
+    commands.remove_resource::<DoorArrival>();
+
+    let Some((_, marker_transform)) = marker_query
+        .iter()
+        .find(|(marker, _)| marker.label == arrival.marker)
+    else {
+        warn!("Door leads to unknown marker `{}`", arrival.marker);
+        return;
+    };
+    if let Ok(mut transform) = transforms.get_mut(arrival.entity) {
+        let position = marker_transform.translation.xy();
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
+
+fn read_sign(
+    interact: On<Interact>,
+    mut commands: Commands,
+    sign_query: Query<&Sign>,
+    mut scripts: ResMut<Assets<DialogueScript>>,
+) {
+    let Ok(sign) = sign_query.get(interact.entity) else {
+        return;
+    };
+    let lines = sign
+        .text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        return;
+    }
+    commands.trigger(StartDialogue {
+        script: scripts.add(DialogueScript::message(lines)),
+        speaker: sign.name.clone(),
+    });
+}
@@ -0,0 +1,426 @@
+//! Quests described in `.quests.ron` asset files, and the player's progress on
+//! them.
+//!
+//! Every quest has an id, which dialogue starts it by (see [`StartQuest`]), a
+//! list of objectives and rewards for completing them all:
+//!
+//! ```ron
+//! (
+//!     quests: {
+//!         "slime_trouble": (
+//!             name: "Slime Trouble",
+//!             description: "The elder asked you to get rid of the slime by the old well.",
+//!             objectives: [
+//!                 Reach(marker: "Old Well"),
+//!                 Defeat(enemy: "Slime", count: 1),
+//!                 Collect(item: "apple", count: 3),
+//!             ],
+//!             rewards: (experience: 50, items: [(item: "leather_cap", count: 1)]),
+//!         ),
+//!     },
+//! )
+//! ```
+//!
+//! Progress is kept in the [`QuestLog`], which is part of the save game.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    AppSystems, PausableSystems,
+    asset_tracking::LoadResource,
+    game::{
+        combat::{Defeated, Enemy},
+        experience::GainExperience,
+        flags::GameFlags,
+        inventory::{GiveItem, Inventory, ItemStack},
+        player::Player,
+        tiled_map::MapMarker,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<QuestDefinitions>();
+    app.register_asset_loader(QuestDefinitionsLoader);
+    app.load_resource::<QuestAssets>();
+    app.init_resource::<QuestLog>();
+
+    app.add_observer(start_quest);
+    app.add_observer(count_defeated_enemies);
+    app.add_systems(
+        Update,
+        (track_reached_markers, track_collected_items, complete_quests)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// How close the player has to get to a [`MapMarker`] to reach it.
+const REACH_RADIUS: f32 = 32.0;
+
+/// Something the player has to do to complete a quest.
+#[derive(Debug, Clone, PartialEq, Eq, Reflect, Deserialize)]
+pub enum Objective {
+    /// Get close to the [`MapMarker`] with this label.
+    Reach { marker: String },
+    /// Defeat a number of enemies of a kind.
+    Defeat { enemy: String, count: u32 },
+    /// Carry a number of an item at the same time.
+    Collect { item: String, count: u32 },
+}
+
+impl Objective {
+    /// The progress at which the objective is done.
+    pub fn target(&self) -> u32 {
+        match self {
+            Self::Reach { .. } => 1,
+            Self::Defeat { count, .. } | Self::Collect { count, .. } => *count,
+        }
+    }
+
+    /// A short description for the journal, e.g. `Defeat Slime (1/3)`.
+    pub fn describe(&self, progress: u32) -> String {
+        match self {
+            Self::Reach { marker } => format!("Reach {marker}"),
+            Self::Defeat { enemy, count } => format!("Defeat {enemy} ({progress}/{count})"),
+            Self::Collect { item, count } => format!("Collect {item} ({progress}/{count})"),
+        }
+    }
+}
+
+/// What the player gets for completing a quest.
+#[derive(Debug, Clone, Default, Reflect, Deserialize)]
+#[serde(default)]
+pub struct QuestRewards {
+    pub experience: u32,
+    pub items: Vec<ItemStack>,
+    /// Flags to set, e.g. for dialogue to react to the quest being done.
+    pub flags: Vec<String>,
+}
+
+/// A single quest in a [`QuestDefinitions`] asset.
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct QuestDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub objectives: Vec<Objective>,
+    #[serde(default)]
+    pub rewards: QuestRewards,
+}
+
+/// A set of [`QuestDefinition`]s by id, loaded from a `.quests.ron` file.
+#[derive(Asset, Debug, Reflect, Deserialize)]
+pub struct QuestDefinitions {
+    quests: HashMap<String, QuestDefinition>,
+}
+
+#[derive(Debug, Error)]
+pub enum QuestDefinitionsLoaderError {
+    #[error("Could not load quest file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse quest file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Quest `{0}` has no objectives")]
+    NoObjectives(String),
+}
+
+pub struct QuestDefinitionsLoader;
+
+impl AssetLoader for QuestDefinitionsLoader {
+    type Asset = QuestDefinitions;
+    type Settings = ();
+    type Error = QuestDefinitionsLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        parse_quest_definitions(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["quests.ron"]
+    }
+}
+
+/// Parse quest definitions, checking that every quest has objectives.
+fn parse_quest_definitions(bytes: &[u8]) -> Result<QuestDefinitions, QuestDefinitionsLoaderError> {
+    let definitions: QuestDefinitions = ron::de::from_bytes(bytes)?;
+
+    for (id, quest) in &definitions.quests {
+        if quest.objectives.is_empty() {
+            return Err(QuestDefinitionsLoaderError::NoObjectives(id.clone()));
+        }
+    }
+    Ok(definitions)
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct QuestAssets {
+    #[dependency]
+    pub definitions: Handle<QuestDefinitions>,
+}
+
+impl FromWorld for QuestAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            definitions: assets.load("quests/main.quests.ron"),
+        }
+    }
+}
+
+/// Looks up [`QuestDefinition`]s by id.
+#[derive(SystemParam)]
+pub struct Quests<'w> {
+    quest_assets: Option<Res<'w, QuestAssets>>,
+    definitions: Res<'w, Assets<QuestDefinitions>>,
+}
+
+impl Quests<'_> {
+    /// The definition of a quest, or `None` if there is no such quest or the
+    /// definitions haven't been loaded yet.
+    pub fn get(&self, id: &str) -> Option<&QuestDefinition> {
+        let quest_assets = self.quest_assets.as_ref()?;
+        self.definitions
+            .get(&quest_assets.definitions)?
+            .quests
+            .get(id)
+    }
+}
+
+/// The quests the player has started and completed.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct QuestLog {
+    /// The progress on each objective of the quests in progress, by quest id.
+    active: BTreeMap<String, Vec<u32>>,
+    completed: BTreeSet<String>,
+}
+
+impl QuestLog {
+    /// The progress on each objective of the quests in progress, by quest id.
+    pub fn active(&self) -> &BTreeMap<String, Vec<u32>> {
+        &self.active
+    }
+
+    pub fn completed(&self) -> &BTreeSet<String> {
+        &self.completed
+    }
+}
+
+/// Start a quest, unless it has already been started.
+#[derive(Event, Debug, Clone)]
+pub struct StartQuest {
+    pub quest: String,
+}
+
+fn start_quest(start: On<StartQuest>, quests: Quests, mut quest_log: ResMut<QuestLog>) {
+    let Some(quest) = quests.get(&start.quest) else {
+        warn!("Can't start unknown quest `{}`", start.quest);
+        return;
+    };
+    if quest_log.active.contains_key(&start.quest) || quest_log.completed.contains(&start.quest) {
+        return;
+    }
+    quest_log
+        .active
+        .insert(start.quest.clone(), vec![0; quest.objectives.len()]);
+}
+
+/// Set the progress of every objective of the active quests to what `progress`
+/// returns for it, only marking the quest log as changed if anything changes.
+fn update_progress(
+    quest_log: &mut ResMut<QuestLog>,
+    quests: &Quests,
+    progress: impl Fn(&Objective, u32) -> u32,
+) {
+    let mut changed = Vec::new();
+    for (id, objectives_progress) in &quest_log.active {
+        let Some(quest) = quests.get(id) else {
+            continue;
+        };
+        for (index, objective) in quest.objectives.iter().enumerate() {
+            let Some(&current) = objectives_progress.get(index) else {
+                continue;
+            };
+            let new = progress(objective, current).min(objective.target());
+            if new != current {
+                changed.push((id.clone(), index, new));
+            }
+        }
+    }
+    for (id, index, new) in changed {
+        if let Some(objectives_progress) = quest_log.active.get_mut(&id) {
+            objectives_progress[index] = new;
+        }
+    }
+}
+
+fn count_defeated_enemies(
+    defeated: On<Defeated>,
+    quests: Quests,
+    enemy_query: Query<&Enemy>,
+    player_query: Query<(), With<Player>>,
+    mut quest_log: ResMut<QuestLog>,
+) {
+    let Ok(enemy) = enemy_query.get(defeated.entity) else {
+        return;
+    };
+    if !defeated.by.is_some_and(|by| player_query.contains(by)) {
+        return;
+    }
+    update_progress(&mut quest_log, &quests, |objective, progress| match objective {
+        Objective::Defeat { enemy: kind, .. } if *kind == enemy.kind => progress + 1,
+        _ => progress,
+    });
+}
+
+fn track_reached_markers(
+    quests: Quests,
+    player: Single<&GlobalTransform, With<Player>>,
+    marker_query: Query<(&MapMarker, &GlobalTransform)>,
+    mut quest_log: ResMut<QuestLog>,
+) {
+    let position = player.translation().xy();
+    let reached = marker_query
+        .iter()
+        .filter(|(_, transform)| transform.translation().xy().distance(position) <= REACH_RADIUS)
+        .map(|(marker, _)| marker.label.as_str())
+        .collect::<Vec<_>>();
+    if reached.is_empty() {
+        return;
+    }
+    update_progress(&mut quest_log, &quests, |objective, progress| match objective {
+        Objective::Reach { marker } if reached.contains(&marker.as_str()) => 1,
+        _ => progress,
+    });
+}
+
+/// Count the items the player carries for `Collect` objectives. The progress
+/// goes down again if the items are dropped before the quest is done.
+fn track_collected_items(
+    quests: Quests,
+    inventory: Single<Ref<Inventory>, With<Player>>,
+    mut quest_log: ResMut<QuestLog>,
+) {
+    if !inventory.is_changed() && !quest_log.is_changed() {
+        return;
+    }
+    update_progress(&mut quest_log, &quests, |objective, progress| match objective {
+        Objective::Collect { item, .. } => {
+            let stacks = inventory.stacks().iter().filter(|stack| stack.item == *item);
+            let equipped = inventory.equipped().values().filter(|equipped| *equipped == item);
+            stacks.map(|stack| stack.count).sum::<u32>() + equipped.count() as u32
+        }
+        _ => progress,
+    });
+}
+
+/// Complete the quests whose objectives are all done, and hand out their
+/// rewards.
+fn complete_quests(
+    mut commands: Commands,
+    quests: Quests,
+    player: Single<Entity, With<Player>>,
+    mut quest_log: ResMut<QuestLog>,
+    mut flags: ResMut<GameFlags>,
+) {
+    if !quest_log.is_changed() {
+        return;
+    }
+    let done = quest_log
+        .active
+        .iter()
+        .filter_map(|(id, progress)| {
+            let quest = quests.get(id)?;
+            let all_done = quest
+                .objectives
+                .iter()
+                .zip(progress)
+                .all(|(objective, progress)| *progress >= objective.target());
+            all_done.then(|| (id.clone(), quest))
+        })
+        .collect::<Vec<_>>();
+
+    for (id, quest) in done {
+        info!("Completed quest `{id}`");
+        quest_log.active.remove(&id);
+        quest_log.completed.insert(id);
+
+        let rewards = &quest.rewards;
+        if rewards.experience > 0 {
+            commands.trigger(GainExperience {
+                entity: *player,
+                amount: rewards.experience,
+            });
+        }
+        for stack in &rewards.items {
+            commands.trigger(GiveItem {
+                entity: *player,
+                stack: stack.clone(),
+            });
+        }
+        for flag in &rewards.flags {
+            flags.set(flag.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Result<QuestDefinitions, QuestDefinitionsLoaderError> {
+        parse_quest_definitions(contents.as_bytes())
+    }
+
+    #[test]
+    fn quests_with_objectives_are_loaded() {
+        let definitions = parse(
+            r#"(quests: {
+                "slimes": (
+                    name: "Slimes",
+                    objectives: [Reach(marker: "Well"), Defeat(enemy: "Slime", count: 2)],
+                    rewards: (experience: 10),
+                ),
+            })"#,
+        )
+        .unwrap();
+        let quest = &definitions.quests["slimes"];
+        assert_eq!(quest.objectives.len(), 2);
+        assert_eq!(quest.rewards.experience, 10);
+        assert!(quest.rewards.items.is_empty());
+    }
+
+    #[test]
+    fn a_quest_without_objectives_is_rejected() {
+        let error = parse(r#"(quests: {"empty": (name: "Empty", objectives: [])})"#);
+        assert!(
+            matches!(error, Err(QuestDefinitionsLoaderError::NoObjectives(id)) if id == "empty")
+        );
+    }
+
+    #[test]
+    fn invalid_ron_is_rejected() {
+        assert!(matches!(
+            parse("(quests: "),
+            Err(QuestDefinitionsLoaderError::Ron(_))
+        ));
+    }
+}
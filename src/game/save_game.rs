@@ -0,0 +1,142 @@
+//! The save game, holding the game's [`GameFlags`] and [`QuestLog`], and the
+//! player's inventory, experience and base stats. Defeated enemies and opened
+//! chests are recorded as flags.
+//!
+//! The save game is loaded when gameplay starts, and saved whenever the game is
+//! paused or left. Triggering [`NewGame`] clears it, along with the explored
+//! tiles.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Pause,
+    game::{
+        experience::Experience,
+        flags::GameFlags,
+        fog_of_war::ExploredTiles,
+        inventory::Inventory,
+        level::spawn_level,
+        player::Player,
+        quests::QuestLog,
+        stats::{StatValues, Stats},
+    },
+    screens::Screen,
+    storage,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(start_new_game);
+
+    // The player is restored, so it has to be spawned first.
+    app.add_systems(OnEnter(Screen::Gameplay), load_game.after(spawn_level));
+
+    app.add_systems(OnEnter(Pause(true)), save_game);
+    app.add_systems(OnExit(Screen::Gameplay), save_game);
+    // Outside of gameplay, the game may not have been loaded yet.
+    app.add_systems(
+        Last,
+        save_game.run_if(in_state(Screen::Gameplay).and(on_message::<AppExit>)),
+    );
+}
+
+/// The storage key the save game is saved under.
+const SAVE_GAME_KEY: &str = "save.ron";
+
+/// Clear the save game, so that the next game starts from scratch.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct NewGame;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SaveGame {
+    flags: GameFlags,
+    quests: QuestLog,
+    /// `None` until the player has been saved, to start with a new player.
+    player: Option<PlayerSave>,
+}
+
+impl SaveGame {
+    /// Read the save game, starting a new game if there is none or it can't be
+    /// read.
+    fn read() -> Self {
+        match storage::read(SAVE_GAME_KEY) {
+            Ok(Some(contents)) => ron::from_str(&contents).unwrap_or_else(|error| {
+                warn!("Failed to parse save game, starting a new game: {error}");
+                Self::default()
+            }),
+            Ok(None) => Self::default(),
+            Err(error) => {
+                warn!("Failed to read save game: {error}");
+                Self::default()
+            }
+        }
+    }
+
+    fn write(&self) {
+        let contents = match ron::to_string(self) {
+            Ok(contents) => contents,
+            Err(error) => {
+                error!("Failed to serialize save game: {error}");
+                return;
+            }
+        };
+        if let Err(error) = storage::write(SAVE_GAME_KEY, &contents) {
+            warn!("Failed to write save game: {error}");
+        }
+    }
+}
+
+/// What is saved of the [`Player`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PlayerSave {
+    inventory: Inventory,
+    experience: Experience,
+    base_stats: StatValues,
+}
+
+fn load_game(
+    mut flags: ResMut<GameFlags>,
+    mut quest_log: ResMut<QuestLog>,
+    mut player_query: Query<(&mut Inventory, &mut Experience, &mut Stats), With<Player>>,
+) {
+    let save_game = SaveGame::read();
+    flags.set_if_neq(save_game.flags);
+    quest_log.set_if_neq(save_game.quests);
+
+    if let Some(player_save) = save_game.player
+        && let Ok((mut inventory, mut experience, mut stats)) = player_query.single_mut()
+    {
+        *inventory = player_save.inventory;
+        *experience = player_save.experience;
+        stats.base = player_save.base_stats;
+    }
+}
+
+fn save_game(
+    flags: Res<GameFlags>,
+    quest_log: Res<QuestLog>,
+    player_query: Query<(&Inventory, &Experience, &Stats), With<Player>>,
+) {
+    let player = match player_query.single() {
+        Ok((inventory, experience, stats)) => Some(PlayerSave {
+            inventory: inventory.clone(),
+            experience: *experience,
+            base_stats: stats.base,
+        }),
+        // The player may already be gone when gameplay is left, so keep the
+        // one saved before.
+        Err(_) => SaveGame::read().player,
+    };
+    SaveGame {
+        flags: flags.clone(),
+        quests: quest_log.clone(),
+        player,
+    }
+    .write();
+}
+
+fn start_new_game(_: On<NewGame>, mut explored: ResMut<ExploredTiles>) {
+    SaveGame::default().write();
+    *explored = ExploredTiles::default();
+}
@@ -0,0 +1,434 @@
+//! Sprite animations described in `.anim.ron` asset files.
+//!
+//! A [`SpriteAnimations`] asset is a set of named [`AnimationClip`]s, each a
+//! row-major grid of frames in a spritesheet. All of the spritesheets are packed
+//! into a single texture atlas when the asset is loaded, so switching clips only
+//! changes the atlas index, and sprites sharing the asset are batched together:
+//!
+//! ```ron
+//! (
+//!     clips: {
+//!         "run_east": (
+//!             sheet: "images/player/run/run_east.png",
+//!             frame_size: (96, 80),
+//!             columns: 8,
+//!             frames: 8,
+//!             frame_duration_ms: 50,
+//!             mode: Loop,
+//!             events: {
+//!                 2: ["footstep"],
+//!                 5: ["footstep"],
+//!             },
+//!         ),
+//!     },
+//! )
+//! ```
+//!
+//! Any entity with a [`SpriteAnimator`] plays one clip at a time, and its
+//! [`Sprite`] is kept in sync with the current frame. Whenever a frame with
+//! events is reached, an [`AnimationEvent`] is triggered on the entity for each
+//! of them, so gameplay and audio can hook into animations with observers.
+//!
+//! Clips for characters that face different ways are named `{prefix}_{direction}`,
+//! e.g. `run_north_east` (see [`CompassDirection::name`]). Not every direction
+//! needs its own clip: [`SpriteAnimations::directional_clip`] falls back to a
+//! mirrored or neighboring direction.
+//!
+//! One-shot clips (`Once` or `Hold`) can also set a `priority` and
+//! `lock_movement`, which decide how they play over a character's looping
+//! clips (see the `animation` module).
+
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadDirectError, io::Reader},
+    image::TextureAtlasBuilderError,
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{AppSystems, PausableSystems, game::facing::CompassDirection};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<SpriteAnimations>();
+    app.register_asset_loader(SpriteAnimationsLoader);
+
+    app.add_systems(
+        Update,
+        tick_sprite_animators
+            .in_set(AppSystems::TickTimers)
+            .in_set(PausableSystems),
+    );
+    // Run after `Update`, so that clips changed this frame are shown this frame.
+    app.add_systems(PostUpdate, update_animated_sprites);
+}
+
+/// What a clip does after its last frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect, Deserialize)]
+pub enum AnimationMode {
+    /// Start again from the first frame.
+    #[default]
+    Loop,
+    /// Stop on the last frame. See [`SpriteAnimator::is_finished`].
+    Once,
+    /// Stop on the last frame for good, e.g. for a death. Unlike [`Self::Once`],
+    /// the clip never counts as finished.
+    Hold,
+}
+
+/// A single animation in a [`SpriteAnimations`] asset.
+#[derive(Debug, Clone, Reflect)]
+pub struct AnimationClip {
+    /// The index of the clip's first frame in the atlas. The other frames follow it.
+    pub first_frame: usize,
+    pub frames: usize,
+    pub frame_duration: Duration,
+    pub mode: AnimationMode,
+    /// The names of the events to trigger when each frame is reached.
+    pub events: HashMap<usize, Vec<String>>,
+    /// Which one-shot clips this one can interrupt, and be interrupted by: only
+    /// clips with a higher priority interrupt it.
+    pub priority: u32,
+    /// Whether the character can't move while the clip plays.
+    pub locks_movement: bool,
+}
+
+/// A set of named [`AnimationClip`]s, loaded from a `.anim.ron` file.
+#[derive(Asset, Debug, Reflect)]
+pub struct SpriteAnimations {
+    /// Every frame of every clip, packed into one image.
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    clips: HashMap<String, AnimationClip>,
+}
+
+impl SpriteAnimations {
+    pub fn clip(&self, name: &str) -> Option<&AnimationClip> {
+        self.clips.get(name)
+    }
+
+    /// The name of the clip to play for `prefix` facing `direction`, and whether
+    /// it has to be mirrored horizontally, following [`CompassDirection::fallbacks`].
+    pub fn directional_clip(
+        &self,
+        prefix: &str,
+        direction: CompassDirection,
+    ) -> Option<(String, bool)> {
+        direction
+            .fallbacks()
+            .into_iter()
+            .map(|(direction, flip_x)| (format!("{prefix}_{}", direction.name()), flip_x))
+            .find(|(clip, _)| self.clips.contains_key(clip))
+    }
+}
+
+/// The contents of a `.anim.ron` file.
+#[derive(Deserialize)]
+struct SpriteAnimationsFile {
+    clips: HashMap<String, AnimationClipFile>,
+}
+
+#[derive(Deserialize)]
+struct AnimationClipFile {
+    /// The spritesheet, relative to the assets folder.
+    sheet: String,
+    frame_size: UVec2,
+    columns: u32,
+    #[serde(default = "one")]
+    rows: u32,
+    frames: usize,
+    frame_duration_ms: u64,
+    #[serde(default)]
+    mode: AnimationMode,
+    #[serde(default)]
+    events: HashMap<usize, Vec<String>>,
+    #[serde(default)]
+    priority: u32,
+    #[serde(default)]
+    lock_movement: bool,
+}
+
+fn one() -> u32 {
+    1
+}
+
+#[derive(Debug, Error)]
+pub enum SpriteAnimationsLoaderError {
+    #[error("Could not load animation file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse animation file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Could not load spritesheet: {0}")]
+    Sheet(#[from] LoadDirectError),
+    #[error("Could not pack spritesheets into a texture atlas: {0}")]
+    Atlas(#[from] TextureAtlasBuilderError),
+    #[error("Clip `{0}` has a frame grid larger than its spritesheet")]
+    SheetTooSmall(String),
+    #[error("Clip `{0}` has more frames than fit in its spritesheet grid")]
+    TooManyFrames(String),
+    #[error("Clip `{0}` has no frames, or a frame duration of zero")]
+    EmptyClip(String),
+    #[error("Clip `{0}` has events on frame {1}, which is past its last frame")]
+    EventOutOfRange(String, usize),
+}
+
+pub struct SpriteAnimationsLoader;
+
+impl AssetLoader for SpriteAnimationsLoader {
+    type Asset = SpriteAnimations;
+    type Settings = ();
+    type Error = SpriteAnimationsLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: SpriteAnimationsFile = ron::de::from_bytes(&bytes)?;
+
+        // Sort the clips, so that the atlas is laid out the same way every time.
+        let mut file_clips: Vec<_> = file.clips.into_iter().collect();
+        file_clips.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (name, clip) in &file_clips {
+            if clip.frames == 0 || clip.frame_duration_ms == 0 {
+                return Err(SpriteAnimationsLoaderError::EmptyClip(name.clone()));
+            }
+            if clip.frames > (clip.columns * clip.rows) as usize {
+                return Err(SpriteAnimationsLoaderError::TooManyFrames(name.clone()));
+            }
+            if let Some(&frame) = clip.events.keys().find(|&&frame| frame >= clip.frames) {
+                return Err(SpriteAnimationsLoaderError::EventOutOfRange(
+                    name.clone(),
+                    frame,
+                ));
+            }
+        }
+
+        // Load each spritesheet once, even if several clips share it, and pack
+        // them all into one atlas.
+        let mut sheet_indices = HashMap::new();
+        let mut sheets = Vec::new();
+        for (_, clip) in &file_clips {
+            if sheet_indices.contains_key(&clip.sheet) {
+                continue;
+            }
+            let sheet = load_context
+                .loader()
+                .immediate()
+                .load::<Image>(&clip.sheet)
+                .await?;
+            sheet_indices.insert(clip.sheet.clone(), sheets.len());
+            sheets.push(sheet);
+        }
+        let mut builder = TextureAtlasBuilder::default();
+        // Keep frames from bleeding into each other when scaled.
+        builder.padding(UVec2::ONE);
+        for sheet in &sheets {
+            builder.add_texture(None, sheet.get());
+        }
+        let (sheet_layout, _, atlas) = builder.build()?;
+
+        // Lay out the frames of each clip one after another.
+        let mut layout = TextureAtlasLayout::new_empty(sheet_layout.size);
+        let mut clips = HashMap::new();
+        for (name, clip) in file_clips {
+            let sheet_rect = sheet_layout.textures[sheet_indices[&clip.sheet]];
+            let grid_size = clip.frame_size * UVec2::new(clip.columns, clip.rows);
+            if grid_size.cmpgt(sheet_rect.size()).any() {
+                return Err(SpriteAnimationsLoaderError::SheetTooSmall(name));
+            }
+
+            let first_frame = layout.len();
+            for frame in 0..clip.frames as u32 {
+                let min = sheet_rect.min
+                    + UVec2::new(frame % clip.columns, frame / clip.columns) * clip.frame_size;
+                layout.add_texture(URect::from_corners(min, min + clip.frame_size));
+            }
+            let clip = AnimationClip {
+                first_frame,
+                frames: clip.frames,
+                frame_duration: Duration::from_millis(clip.frame_duration_ms),
+                mode: clip.mode,
+                events: clip.events,
+                priority: clip.priority,
+                locks_movement: clip.lock_movement,
+            };
+            clips.insert(name, clip);
+        }
+
+        Ok(SpriteAnimations {
+            image: load_context.add_labeled_asset("atlas".to_string(), atlas),
+            layout: load_context.add_labeled_asset("layout".to_string(), layout),
+            clips,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim.ron"]
+    }
+}
+
+/// Plays [`AnimationClip`]s from a [`SpriteAnimations`] asset on the entity's [`Sprite`].
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+#[require(Sprite)]
+pub struct SpriteAnimator {
+    animations: Handle<SpriteAnimations>,
+    clip: String,
+    frame: usize,
+    /// How long the current frame has been shown.
+    elapsed: Duration,
+    finished: bool,
+    /// Whether the sprite is mirrored horizontally.
+    flip_x: bool,
+    /// Whether the clip was (re)started since the last tick, so the events of
+    /// its first frame haven't been triggered yet.
+    started: bool,
+}
+
+impl SpriteAnimator {
+    pub fn new(animations: Handle<SpriteAnimations>, clip: impl Into<String>) -> Self {
+        Self {
+            animations,
+            clip: clip.into(),
+            frame: 0,
+            elapsed: Duration::ZERO,
+            finished: false,
+            flip_x: false,
+            started: true,
+        }
+    }
+
+    /// Switch to `clip`, starting from its first frame. Does nothing if it is
+    /// already playing.
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            self.clip = clip.to_string();
+            self.restart();
+        }
+    }
+
+    /// Mirror the sprite horizontally, e.g. to play a west-facing clip as east-facing.
+    pub fn set_flip_x(&mut self, flip_x: bool) {
+        self.flip_x = flip_x;
+    }
+
+    /// Play the current clip again from its first frame.
+    pub fn restart(&mut self) {
+        self.frame = 0;
+        self.elapsed = Duration::ZERO;
+        self.finished = false;
+        self.started = true;
+    }
+
+    pub fn animations(&self) -> &Handle<SpriteAnimations> {
+        &self.animations
+    }
+
+    /// The name of the clip being played.
+    pub fn clip(&self) -> &str {
+        &self.clip
+    }
+
+    /// Whether a [`AnimationMode::Once`] clip has shown its last frame for its
+    /// full duration.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Triggered on an entity with a [`SpriteAnimator`] when its clip reaches a frame
+/// with events, once for each event name.
+#[derive(EntityEvent, Debug, Clone)]
+pub struct AnimationEvent {
+    pub entity: Entity,
+    /// The name of the event, e.g. `footstep`.
+    pub name: String,
+}
+
+fn tick_sprite_animators(
+    mut commands: Commands,
+    time: Res<Time>,
+    animations: Res<Assets<SpriteAnimations>>,
+    mut animators: Query<(Entity, &mut SpriteAnimator)>,
+) {
+    for (entity, mut animator) in &mut animators {
+        let Some(clip) = animations
+            .get(&animator.animations)
+            .and_then(|animations| animations.clip(&animator.clip))
+        else {
+            continue;
+        };
+
+        let mut reached = Vec::new();
+        if animator.started {
+            animator.started = false;
+            reached.push(animator.frame);
+        }
+
+        if !animator.finished {
+            animator.elapsed += time.delta();
+        }
+        while !animator.finished && animator.elapsed >= clip.frame_duration {
+            animator.elapsed -= clip.frame_duration;
+            if animator.frame + 1 < clip.frames {
+                animator.frame += 1;
+            } else if clip.mode == AnimationMode::Loop {
+                animator.frame = 0;
+            } else if clip.mode == AnimationMode::Hold {
+                animator.elapsed = Duration::ZERO;
+                break;
+            } else {
+                animator.finished = true;
+                animator.elapsed = Duration::ZERO;
+                break;
+            }
+            reached.push(animator.frame);
+        }
+
+        for frame in reached {
+            for name in clip.events.get(&frame).into_iter().flatten() {
+                commands.trigger(AnimationEvent {
+                    entity,
+                    name: name.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Update the sprite's image and texture atlas to show the animator's current frame.
+fn update_animated_sprites(
+    animations: Res<Assets<SpriteAnimations>>,
+    mut query: Query<(&SpriteAnimator, &mut Sprite), Changed<SpriteAnimator>>,
+) {
+    for (animator, mut sprite) in &mut query {
+        let Some(animations) = animations.get(&animator.animations) else {
+            continue;
+        };
+        let Some(clip) = animations.clip(&animator.clip) else {
+            continue;
+        };
+        let index = clip.first_frame + animator.frame;
+
+        if sprite.flip_x != animator.flip_x {
+            sprite.flip_x = animator.flip_x;
+        }
+        if sprite.image != animations.image {
+            sprite.image = animations.image.clone();
+        }
+        match sprite.texture_atlas.as_mut() {
+            Some(atlas) if atlas.layout == animations.layout => atlas.index = index,
+            _ => {
+                sprite.texture_atlas = Some(TextureAtlas {
+                    layout: animations.layout.clone(),
+                    index,
+                });
+            }
+        }
+    }
+}
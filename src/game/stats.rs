@@ -0,0 +1,247 @@
+//! Character stats, and the modifiers that equipment and buffs put on them.
+//!
+//! A [`Stats`] component holds base values, which grow with levels, and the
+//! current values after [`StatModifier`]s. Modifiers stack: all `Add` modifiers
+//! of a stat are added to its base value first, and the sum is then multiplied
+//! by every `Multiply` modifier. Current values set the character's
+//! [`MovementController::max_speed`] and [`Health::max`], and decide how much
+//! damage it deals and takes.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{combat::Health, movement::MovementController},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            tick_buffs.in_set(AppSystems::TickTimers),
+            update_stats.in_set(AppSystems::Update),
+        )
+            .in_set(PausableSystems),
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Deserialize)]
+pub enum Stat {
+    /// Raises the damage of attacks.
+    Strength,
+    /// Lowers the damage taken from attacks.
+    Defense,
+    /// The maximum movement speed, in world units per second.
+    Speed,
+    MaxHealth,
+}
+
+/// A change to a stat, e.g. from an equipped item or a potion.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, Deserialize)]
+pub enum StatModifier {
+    Add(Stat, f32),
+    Multiply(Stat, f32),
+}
+
+/// A value for each [`Stat`].
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct StatValues {
+    pub strength: f32,
+    pub defense: f32,
+    pub speed: f32,
+    pub max_health: f32,
+}
+
+impl StatValues {
+    fn get_mut(&mut self, stat: Stat) -> &mut f32 {
+        match stat {
+            Stat::Strength => &mut self.strength,
+            Stat::Defense => &mut self.defense,
+            Stat::Speed => &mut self.speed,
+            Stat::MaxHealth => &mut self.max_health,
+        }
+    }
+
+    /// The values after applying `modifiers`.
+    fn modified(&self, modifiers: &[StatModifier]) -> Self {
+        let mut values = *self;
+        for modifier in modifiers {
+            if let StatModifier::Add(stat, amount) = *modifier {
+                *values.get_mut(stat) += amount;
+            }
+        }
+        for modifier in modifiers {
+            if let StatModifier::Multiply(stat, factor) = *modifier {
+                *values.get_mut(stat) *= factor;
+            }
+        }
+        values
+    }
+}
+
+/// A character's stats.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+#[require(Buffs, EquipmentModifiers)]
+pub struct Stats {
+    /// The stats without any modifiers.
+    pub base: StatValues,
+    current: StatValues,
+}
+
+impl Stats {
+    pub fn new(base: StatValues) -> Self {
+        Self {
+            base,
+            current: base,
+        }
+    }
+
+    /// The damage of the character's attacks.
+    pub fn attack_damage(&self) -> f32 {
+        self.current.strength
+    }
+
+    /// How much of an attack's `damage` the character takes. Each point of
+    /// defense helps a little less than the one before.
+    pub fn damage_taken(&self, damage: f32) -> f32 {
+        damage * 100.0 / (100.0 + self.current.defense.max(0.0))
+    }
+}
+
+/// Modifiers that wear off after a while, e.g. from potions.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Buffs(Vec<Buff>);
+
+impl Buffs {
+    pub fn add(&mut self, modifiers: Vec<StatModifier>, duration: Duration) {
+        self.0.push(Buff {
+            modifiers,
+            timer: Timer::new(duration, TimerMode::Once),
+        });
+    }
+}
+
+#[derive(Debug, Reflect)]
+struct Buff {
+    modifiers: Vec<StatModifier>,
+    timer: Timer,
+}
+
+/// The modifiers of a character's equipped items.
+#[derive(Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct EquipmentModifiers(pub Vec<StatModifier>);
+
+fn tick_buffs(time: Res<Time>, mut buffs_query: Query<&mut Buffs>) {
+    for mut buffs in &mut buffs_query {
+        if buffs.0.is_empty() {
+            continue;
+        }
+        for buff in &mut buffs.0 {
+            buff.timer.tick(time.delta());
+        }
+        buffs.0.retain(|buff| !buff.timer.is_finished());
+    }
+}
+
+/// Recompute current stats when the base stats or modifiers change, and apply
+/// them to movement and health.
+fn update_stats(
+    mut query: Query<
+        (
+            &mut Stats,
+            &Buffs,
+            &EquipmentModifiers,
+            Option<&mut MovementController>,
+            Option<&mut Health>,
+        ),
+        Or<(Changed<Stats>, Changed<Buffs>, Changed<EquipmentModifiers>)>,
+    >,
+) {
+    for (mut stats, buffs, equipment, controller, health) in &mut query {
+        let modifiers = buffs
+            .0
+            .iter()
+            .flat_map(|buff| buff.modifiers.iter().copied())
+            .chain(equipment.0.iter().copied())
+            .collect::<Vec<_>>();
+        let current = stats.base.modified(&modifiers);
+        if stats.current != current {
+            stats.current = current;
+        }
+
+        if let Some(mut controller) = controller
+            && controller.max_speed != current.speed
+        {
+            controller.max_speed = current.speed;
+        }
+        if let Some(mut health) = health
+            && health.max != current.max_health
+        {
+            health.set_max(current.max_health);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: StatValues = StatValues {
+        strength: 10.0,
+        defense: 5.0,
+        speed: 200.0,
+        max_health: 100.0,
+    };
+
+    #[test]
+    fn no_modifiers_keep_the_base_values() {
+        assert_eq!(BASE.modified(&[]), BASE);
+    }
+
+    #[test]
+    fn additions_are_summed_before_multiplying() {
+        let modified = BASE.modified(&[
+            StatModifier::Multiply(Stat::Speed, 1.5),
+            StatModifier::Add(Stat::Speed, 50.0),
+            StatModifier::Multiply(Stat::Speed, 2.0),
+            StatModifier::Add(Stat::Speed, 50.0),
+        ]);
+        assert_eq!(modified.speed, (200.0 + 50.0 + 50.0) * 1.5 * 2.0);
+    }
+
+    #[test]
+    fn modifiers_only_change_their_own_stat() {
+        let modified = BASE.modified(&[
+            StatModifier::Add(Stat::Strength, 2.0),
+            StatModifier::Multiply(Stat::MaxHealth, 0.5),
+        ]);
+        assert_eq!(
+            modified,
+            StatValues {
+                strength: 12.0,
+                max_health: 50.0,
+                ..BASE
+            }
+        );
+    }
+
+    #[test]
+    fn defense_reduces_damage_taken() {
+        let stats = Stats::new(StatValues {
+            defense: 100.0,
+            ..BASE
+        });
+        assert_eq!(stats.damage_taken(30.0), 15.0);
+        let no_defense = Stats::new(StatValues {
+            defense: -10.0,
+            ..BASE
+        });
+        assert_eq!(no_defense.damage_taken(30.0), 30.0);
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     io::{Cursor, ErrorKind},
     sync::Arc,
 };
@@ -12,10 +12,25 @@ use bevy_ecs_tilemap::TilemapPlugin;
 use bevy_ecs_tilemap::prelude::*;
 use thiserror::Error;
 
+use crate::{
+    audio::spatial_ambient,
+    game::{
+        combat::{DefeatedFlag, EnemyDrops, enemy},
+        dialogue::{Npc, npc},
+        flags::GameFlags,
+        inventory::{ItemStack, Pickup, pickup},
+        props::{Chest, Door, Sign, chest, door, sign},
+    },
+};
+
 pub(super) fn plugin(app: &mut App) {
     app.register_asset_loader(TiledLoader);
     app.add_plugins(TilemapPlugin);
     app.init_resource::<CollisionTiles>();
+    app.init_resource::<SurfaceTiles>();
+    app.init_resource::<MapBounds>();
+    app.init_resource::<MapEdgeMode>();
+    app.init_resource::<LoadedMapPath>();
     app.add_systems(Update, process_loaded_maps);
 }
 
@@ -42,6 +57,31 @@ pub struct TiledMapBundle {
     pub render_settings: TilemapRenderSettings,
 }
 
+/// A looping sound spawned from an object in one of the map's object layers.
+///
+/// Any object with a `sound` string property becomes an emitter, playing the
+/// asset at that path (e.g. `audio/ambient/river.ogg`) from the object's position.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct MapSoundEmitter;
+
+/// Marks an entity spawned from an object in one of the map's object layers.
+/// Map objects are despawned when the map is reloaded or another map is loaded.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct MapObject;
+
+/// A point of interest shown on the minimap, spawned from an object in one of
+/// the map's object layers.
+///
+/// Any object with a `marker` string property becomes a marker, labeled with
+/// the property's value (e.g. `Old Well`).
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct MapMarker {
+    pub label: String,
+}
+
 #[derive(Resource, Default, Debug, Clone)]
 pub struct CollisionTiles {
     pub blocked: HashSet<IVec2>,
@@ -50,6 +90,136 @@ pub struct CollisionTiles {
     pub layer_offset: Vec2,
 }
 
+impl CollisionTiles {
+    /// The collision tile under a world position.
+    pub fn tile_at(&self, world: Vec2) -> IVec2 {
+        world_to_iso_tile(world, self.map_size, self.grid_size, self.layer_offset)
+    }
+
+    /// The logical map coordinates of a world position, including how far into
+    /// its tile it is.
+    pub fn coords_at(&self, world: Vec2) -> Vec2 {
+        world_to_iso_coords(world, self.map_size, self.grid_size, self.layer_offset)
+    }
+
+    /// The world position of logical map coordinates, the inverse of
+    /// [`Self::coords_at`].
+    pub fn world_at(&self, coords: Vec2) -> Vec2 {
+        iso_coords_to_world(coords, self.map_size, self.grid_size, self.layer_offset)
+    }
+
+    /// Whether a tile is part of the map. Always true before a map is loaded.
+    pub fn in_map(&self, tile: IVec2) -> bool {
+        self.map_size == UVec2::ZERO
+            || (tile.cmpge(IVec2::ZERO) & tile.cmplt(self.map_size.as_ivec2())).all()
+    }
+
+    /// Move a world position outside the map by whole map sizes, so that it
+    /// comes back in on the opposite edge.
+    pub fn wrap(&self, world: Vec2) -> Vec2 {
+        if self.map_size == UVec2::ZERO {
+            return world;
+        }
+        let tile = self.tile_at(world);
+        let shift = (tile.rem_euclid(self.map_size.as_ivec2()) - tile).as_vec2();
+        // The world directions of one step along each tile axis.
+        let half_grid = self.grid_size * 0.5;
+        let x_axis = half_grid;
+        let y_axis = Vec2::new(-half_grid.x, half_grid.y);
+        world + shift.x * x_axis + shift.y * y_axis
+    }
+}
+
+/// The asset path of the map that [`CollisionTiles`], [`SurfaceTiles`] and
+/// [`MapBounds`] were last built from, or `None` before a map has been loaded.
+#[derive(Resource, Default, Debug, Clone, PartialEq)]
+pub struct LoadedMapPath(pub Option<String>);
+
+/// What happens to a [`MapBoundary`](crate::game::movement::MapBoundary)
+/// entity at the edge of the map, set with an `edge` string property on the map
+/// (`contain`, `wrap` or `transition`).
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Resource)]
+pub enum MapEdgeMode {
+    /// Stop at the edge.
+    #[default]
+    Contain,
+    /// Come back in on the opposite edge.
+    Wrap,
+    /// Stop at the edge, and trigger
+    /// [`MapEdgeReached`](crate::game::movement::MapEdgeReached), e.g. to
+    /// load a neighboring map.
+    Transition,
+}
+
+impl MapEdgeMode {
+    fn from_property(value: &str) -> Option<Self> {
+        match value {
+            "contain" => Some(Self::Contain),
+            "wrap" => Some(Self::Wrap),
+            "transition" => Some(Self::Transition),
+            _ => None,
+        }
+    }
+}
+
+/// What the ground is made of, set with a `surface` string property on tileset
+/// tiles (`grass`, `stone` or `water`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect)]
+pub enum Surface {
+    /// Tiles without a `surface` property.
+    #[default]
+    Default,
+    Grass,
+    Stone,
+    Water,
+}
+
+impl Surface {
+    fn from_property(value: &str) -> Option<Self> {
+        match value {
+            "grass" => Some(Self::Grass),
+            "stone" => Some(Self::Stone),
+            "water" => Some(Self::Water),
+            _ => None,
+        }
+    }
+}
+
+/// The [`Surface`] of every tile that has one.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct SurfaceTiles {
+    /// The tiles of each layer with surfaces, keyed by layer index.
+    layers: BTreeMap<usize, SurfaceLayer>,
+    map_size: UVec2,
+    grid_size: Vec2,
+}
+
+#[derive(Default, Debug, Clone)]
+struct SurfaceLayer {
+    offset: Vec2,
+    tiles: HashMap<IVec2, Surface>,
+}
+
+impl SurfaceTiles {
+    /// The surface under a world position, from the topmost layer that has one there.
+    pub fn surface_at(&self, world: Vec2) -> Surface {
+        self.layers
+            .values()
+            .rev()
+            .find_map(|layer| {
+                let tile = world_to_iso_tile(world, self.map_size, self.grid_size, layer.offset);
+                layer.tiles.get(&tile).copied()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// The area of the world covered by the current map's tile layers, or `None`
+/// before a map has been loaded.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq)]
+pub struct MapBounds(pub Option<Rect>);
+
 pub struct BytesResourceReader {
     bytes: Arc<[u8]>,
 }
@@ -144,16 +314,24 @@ impl AssetLoader for TiledLoader {
 
 pub fn process_loaded_maps(
     mut commands: Commands,
+    asset_server: Res<AssetServer>,
     mut map_events: MessageReader<AssetEvent<TiledMap>>,
     maps: Res<Assets<TiledMap>>,
     tile_storage_query: Query<(Entity, &TileStorage)>,
+    map_objects: Query<(Entity, &ChildOf), With<MapObject>>,
     mut map_query: Query<(
+        Entity,
         &TiledMapHandle,
         &mut TiledLayersStorage,
         &mut TilemapRenderSettings,
     )>,
-    new_maps: Query<&TiledMapHandle, Added<TiledMapHandle>>,
+    new_maps: Query<&TiledMapHandle, Changed<TiledMapHandle>>,
     mut collisions: ResMut<CollisionTiles>,
+    mut surfaces: ResMut<SurfaceTiles>,
+    mut map_bounds: ResMut<MapBounds>,
+    mut edge_mode: ResMut<MapEdgeMode>,
+    mut loaded_map: ResMut<LoadedMapPath>,
+    flags: Res<GameFlags>,
 ) {
     let mut changed_maps = Vec::<AssetId<TiledMap>>::default();
     for event in map_events.read() {
@@ -176,13 +354,15 @@ pub fn process_loaded_maps(
         }
     }
 
-    // If we have new map entities add them to the changed_maps list.
+    // If we have new map entities, or ones switched to another map, add them to
+    // the changed_maps list.
     for new_map_handle in new_maps.iter() {
         changed_maps.push(new_map_handle.0.id());
     }
 
     for changed_map in changed_maps.iter() {
-        for (map_handle, mut layer_storage, mut render_settings) in map_query.iter_mut() {
+        for (map_entity, map_handle, mut layer_storage, mut render_settings) in map_query.iter_mut()
+        {
             // only deal with currently changed map
             if map_handle.0.id() != *changed_map {
                 continue;
@@ -195,11 +375,47 @@ pub fn process_loaded_maps(
                             commands.entity(*tile).despawn()
                         }
                     }
-                    // commands.entity(*layer_entity).despawn_recursive();
+                    commands.entity(*layer_entity).despawn();
                 }
+                layer_storage.storage.clear();
+                for (object, child_of) in &map_objects {
+                    if child_of.parent() == map_entity {
+                        commands.entity(object).despawn();
+                    }
+                }
+
+                surfaces.layers.clear();
+                surfaces.map_size = UVec2::new(tiled_map.map.width, tiled_map.map.height);
+                surfaces.grid_size = Vec2::new(
+                    tiled_map.map.tile_width as f32,
+                    tiled_map.map.tile_height as f32,
+                );
 
                 // No overlay entities to clean up when tinting directly
 
+                let edge = match tiled_map.map.properties.get("edge") {
+                    Some(tiled::PropertyValue::StringValue(value)) => {
+                        MapEdgeMode::from_property(value).unwrap_or_else(|| {
+                            warn!("Unknown map edge mode `{value}`, containing instead.");
+                            MapEdgeMode::Contain
+                        })
+                    }
+                    _ => MapEdgeMode::Contain,
+                };
+                edge_mode.set_if_neq(edge);
+
+                // The map's extent, for the map boundary. The collision layer
+                // refines the offset and blocked tiles if the map has one.
+                collisions.blocked.clear();
+                collisions.map_size = UVec2::new(tiled_map.map.width, tiled_map.map.height);
+                collisions.grid_size = Vec2::new(
+                    tiled_map.map.tile_width as f32,
+                    tiled_map.map.tile_height as f32,
+                );
+                collisions.layer_offset = Vec2::ZERO;
+
+                let mut bounds = Rect::EMPTY;
+
                 // The TilemapBundle requires that all tile images come exclusively from a single
                 // tiled texture or from a Vec of independent per-tile images. Furthermore, all of
                 // the per-tile images must be the same size. Since Tiled allows tiles of mixed
@@ -253,18 +469,7 @@ pub fn process_loaded_maps(
                             y: tiled_map.map.tile_height as f32,
                         };
 
-                        let map_type = match tiled_map.map.orientation {
-                            tiled::Orientation::Hexagonal => {
-                                TilemapType::Hexagon(HexCoordSystem::Row)
-                            }
-                            tiled::Orientation::Isometric => {
-                                TilemapType::Isometric(IsoCoordSystem::Diamond)
-                            }
-                            tiled::Orientation::Staggered => {
-                                TilemapType::Isometric(IsoCoordSystem::Staggered)
-                            }
-                            tiled::Orientation::Orthogonal => TilemapType::Square,
-                        };
+                        let map_type = tilemap_type(tiled_map.map.orientation);
 
                         let mut tile_storage = TileStorage::empty(map_size);
                         let layer_entity = commands.spawn_empty().id();
@@ -272,12 +477,6 @@ pub fn process_loaded_maps(
                         // If this is the Collisions layer, rebuild the collision set
                         let is_collision_layer = layer.name == "Collisions";
                         if is_collision_layer {
-                            collisions.blocked.clear();
-                            collisions.map_size = UVec2::new(map_size.x, map_size.y);
-                            collisions.grid_size = Vec2::new(
-                                tiled_map.map.tile_width as f32,
-                                tiled_map.map.tile_height as f32,
-                            );
                             collisions.layer_offset = Vec2::new(offset_x, -offset_y);
                         }
 
@@ -329,12 +528,29 @@ pub fn process_loaded_maps(
 
                                 tile_storage.set(&tile_pos, tile_entity);
 
-                                // Record collision tiles by logical map coordinates
+                                // Record collision and surface tiles by logical map coordinates.
+                                let rotated = logical_tile(tile_pos, map_size.x);
                                 if is_collision_layer {
-                                    // Rotate left (90° CCW) to align collision sampling with visuals
-                                    let width_i = map_size.x as i32;
-                                    let rotated = IVec2::new(y as i32, width_i - 1 - x as i32);
                                     collisions.blocked.insert(rotated);
+                                } else if let Some(surface) =
+                                    layer_tile.get_tile().and_then(|tile| {
+                                        match tile.properties.get("surface") {
+                                            Some(tiled::PropertyValue::StringValue(value)) => {
+                                                Surface::from_property(value)
+                                            }
+                                            _ => None,
+                                        }
+                                    })
+                                {
+                                    surfaces
+                                        .layers
+                                        .entry(layer_index)
+                                        .or_insert_with(|| SurfaceLayer {
+                                            offset: Vec2::new(offset_x, -offset_y),
+                                            ..default()
+                                        })
+                                        .tiles
+                                        .insert(rotated, surface);
                                 }
                             }
                         }
@@ -344,6 +560,14 @@ pub fn process_loaded_maps(
                             render_settings.y_sort = true;
                         }
 
+                        bounds = bounds.union(layer_bounds(
+                            &map_size,
+                            &grid_size,
+                            &tile_size,
+                            &map_type,
+                            Vec2::new(offset_x, -offset_y),
+                        ));
+
                         commands.entity(layer_entity).insert(TilemapBundle {
                             grid_size,
                             size: map_size,
@@ -365,7 +589,410 @@ pub fn process_loaded_maps(
                         // No overlay tilemap needed when tinting directly
                     }
                 }
+
+                map_bounds.set_if_neq(MapBounds((!bounds.is_empty()).then_some(bounds)));
+                let map_path = asset_server
+                    .get_path(map_handle.0.id())
+                    .map(|path| path.to_string());
+                loaded_map.set_if_neq(LoadedMapPath(map_path.clone()));
+
+                spawn_sound_emitters(&mut commands, &asset_server, map_entity, &tiled_map.map);
+                spawn_map_markers(&mut commands, map_entity, &tiled_map.map);
+                spawn_pickups(&mut commands, map_entity, &tiled_map.map);
+                let map_path = map_path.as_deref().unwrap_or_default();
+                spawn_enemies(&mut commands, map_entity, &tiled_map.map, map_path, &flags);
+                spawn_npcs(&mut commands, &asset_server, map_entity, &tiled_map.map);
+                spawn_chests(&mut commands, map_entity, &tiled_map.map, map_path);
+                spawn_doors(&mut commands, map_entity, &tiled_map.map);
+                spawn_signs(&mut commands, map_entity, &tiled_map.map);
             }
         }
     }
 }
+
+/// The logical map coordinates of a tile in a layer of a map `map_width` tiles
+/// wide, as used by [`CollisionTiles`] and [`SurfaceTiles`].
+pub fn logical_tile(tile_pos: TilePos, map_width: u32) -> IVec2 {
+    // Rotate left (90° CCW) to align sampling with visuals.
+    IVec2::new(tile_pos.y as i32, map_width as i32 - 1 - tile_pos.x as i32)
+}
+
+/// The logical map coordinates of the tile under a world position, for a layer
+/// drawn at `layer_offset`.
+fn world_to_iso_tile(world: Vec2, map_size: UVec2, grid_size: Vec2, layer_offset: Vec2) -> IVec2 {
+    world_to_iso_coords(world, map_size, grid_size, layer_offset)
+        .floor()
+        .as_ivec2()
+}
+
+/// The logical map coordinates of a world position, including how far into its
+/// tile it is, for a layer drawn at `layer_offset`.
+fn world_to_iso_coords(world: Vec2, map_size: UVec2, grid_size: Vec2, layer_offset: Vec2) -> Vec2 {
+    let half_w = grid_size.x * 0.5;
+    let half_h = grid_size.y * 0.5;
+
+    // Center of the map in tile coordinates (due to TilemapAnchor::Center)
+    let center_x = (map_size.x as f32 - 1.0) * 0.5;
+    let center_y = (map_size.y as f32 - 1.0) * 0.5;
+
+    // Undo tilemap transform (offset applied at spawn)
+    let local = world - layer_offset;
+
+    // Convert to skewed isometric space
+    let sx = local.x / half_w;
+    let sy = local.y / half_h;
+
+    // Inverse of:
+    // world.x = (x - y - (center_x - center_y)) * half_w + offset_x
+    // world.y = (x + y - (center_x + center_y)) * half_h - offset_y
+    let tx = (sy + sx) * 0.5 + center_x;
+    let ty = (sy - sx) * 0.5 + center_y;
+
+    Vec2::new(tx, ty)
+}
+
+/// The world position of logical map coordinates, the inverse of
+/// [`world_to_iso_coords`].
+fn iso_coords_to_world(coords: Vec2, map_size: UVec2, grid_size: Vec2, layer_offset: Vec2) -> Vec2 {
+    let center = (map_size.as_vec2() - 1.0) * 0.5;
+    let relative = coords - center;
+    let skewed = Vec2::new(relative.x - relative.y, relative.x + relative.y);
+    skewed * grid_size * 0.5 + layer_offset
+}
+
+/// The area covered by the tiles of a layer drawn at `offset`, including the
+/// parts of tile images that stick out of their grid cells.
+fn layer_bounds(
+    map_size: &TilemapSize,
+    grid_size: &TilemapGridSize,
+    tile_size: &TilemapTileSize,
+    map_type: &TilemapType,
+    offset: Vec2,
+) -> Rect {
+    let last = UVec2::new(map_size.x, map_size.y).saturating_sub(UVec2::ONE);
+    let corners = [
+        UVec2::ZERO,
+        UVec2::new(last.x, 0),
+        UVec2::new(0, last.y),
+        last,
+    ];
+    let half_tile = Vec2::new(tile_size.x, tile_size.y) * 0.5;
+    corners
+        .into_iter()
+        .map(|corner| {
+            let center = TilePos::from(corner).center_in_world(
+                map_size,
+                grid_size,
+                tile_size,
+                map_type,
+                &TilemapAnchor::Center,
+            );
+            Rect::from_center_half_size(center + offset, half_tile)
+        })
+        .fold(Rect::EMPTY, |bounds, tile| bounds.union(tile))
+}
+
+fn tilemap_type(orientation: tiled::Orientation) -> TilemapType {
+    match orientation {
+        tiled::Orientation::Hexagonal => TilemapType::Hexagon(HexCoordSystem::Row),
+        tiled::Orientation::Isometric => TilemapType::Isometric(IsoCoordSystem::Diamond),
+        tiled::Orientation::Staggered => TilemapType::Isometric(IsoCoordSystem::Staggered),
+        tiled::Orientation::Orthogonal => TilemapType::Square,
+    }
+}
+
+/// Every object in the map's object layers, with its position relative to the
+/// map entity.
+fn layer_objects(map: &tiled::Map) -> impl Iterator<Item = (tiled::Object<'_>, Vec2)> {
+    map.layers()
+        .filter_map(|layer| match layer.layer_type() {
+            tiled::LayerType::Objects(object_layer) => Some((layer, object_layer)),
+            _ => None,
+        })
+        .flat_map(move |(layer, object_layer)| {
+            let layer_offset = Vec2::new(layer.offset_x, -layer.offset_y);
+            object_layer.objects().map(move |object| {
+                let position = object_position(map, object.x, object.y) + layer_offset;
+                (object, position)
+            })
+        })
+}
+
+/// Spawn a [`MapSoundEmitter`] for every object with a `sound` property.
+fn spawn_sound_emitters(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    map_entity: Entity,
+    map: &tiled::Map,
+) {
+    for (object, position) in layer_objects(map) {
+        let Some(tiled::PropertyValue::StringValue(path)) = object.properties.get("sound") else {
+            continue;
+        };
+        commands.spawn((
+            Name::new(format!("Sound Emitter {}", object.name)),
+            MapSoundEmitter,
+            spatial_ambient(asset_server.load(path.clone()), position.extend(0.0)),
+            MapObject,
+            ChildOf(map_entity),
+        ));
+    }
+}
+
+/// Spawn a [`MapMarker`] for every object with a `marker` property.
+fn spawn_map_markers(commands: &mut Commands, map_entity: Entity, map: &tiled::Map) {
+    for (object, position) in layer_objects(map) {
+        let Some(tiled::PropertyValue::StringValue(label)) = object.properties.get("marker") else {
+            continue;
+        };
+        commands.spawn((
+            Name::new(format!("Map Marker {}", object.name)),
+            MapMarker {
+                label: label.clone(),
+            },
+            Transform::from_translation(position.extend(0.0)),
+            MapObject,
+            ChildOf(map_entity),
+        ));
+    }
+}
+
+/// Spawn a [`Pickup`] for every object with an `item` property, holding the
+/// number of items in its `count` property, or one.
+fn spawn_pickups(commands: &mut Commands, map_entity: Entity, map: &tiled::Map) {
+    for (object, position) in layer_objects(map) {
+        let Some(tiled::PropertyValue::StringValue(item)) = object.properties.get("item") else {
+            continue;
+        };
+        commands.spawn((
+            pickup(
+                Pickup::new(ItemStack {
+                    item: item.clone(),
+                    count: object_count(&object),
+                }),
+                position,
+            ),
+            MapObject,
+            ChildOf(map_entity),
+        ));
+    }
+}
+
+/// Spawn an [`Enemy`](crate::game::combat::Enemy) for every object with an
+/// `enemy` property naming its kind, with the max health in its `health`
+/// property, the experience it grants in its `experience` property and the
+/// items it drops in its `drops` property. Enemies that have been defeated
+/// before are left out.
+fn spawn_enemies(
+    commands: &mut Commands,
+    map_entity: Entity,
+    map: &tiled::Map,
+    map_path: &str,
+    flags: &GameFlags,
+) {
+    for (object, position) in layer_objects(map) {
+        let Some(tiled::PropertyValue::StringValue(kind)) = object.properties.get("enemy") else {
+            continue;
+        };
+        let defeated_flag = format!("enemy_defeated:{map_path}#{}", object.id());
+        if flags.is_set(&defeated_flag) {
+            continue;
+        }
+        let max_health = match object.properties.get("health") {
+            Some(tiled::PropertyValue::IntValue(health)) => (*health).max(1) as f32,
+            _ => 30.0,
+        };
+        let experience = match object.properties.get("experience") {
+            Some(tiled::PropertyValue::IntValue(experience)) => (*experience).max(0) as u32,
+            _ => 0,
+        };
+        commands.spawn((
+            enemy(kind, max_health, experience, position),
+            EnemyDrops(object_drops(&object)),
+            DefeatedFlag(defeated_flag),
+            MapObject,
+            ChildOf(map_entity),
+        ));
+    }
+}
+
+/// Spawn an [`Npc`] for every object with a `dialogue` property, holding the
+/// path of its dialogue script. The NPC is named after the object.
+fn spawn_npcs(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    map_entity: Entity,
+    map: &tiled::Map,
+) {
+    for (object, position) in layer_objects(map) {
+        let Some(tiled::PropertyValue::StringValue(path)) = object.properties.get("dialogue") else {
+            continue;
+        };
+        commands.spawn((
+            npc(
+                Npc {
+                    name: object.name.clone(),
+                    dialogue: asset_server.load(path.clone()),
+                },
+                position,
+            ),
+            MapObject,
+            ChildOf(map_entity),
+        ));
+    }
+}
+
+/// Spawn a [`Chest`] for every object with a `chest` property naming the item
+/// it holds, with the number of items in its `count` property, or one.
+fn spawn_chests(commands: &mut Commands, map_entity: Entity, map: &tiled::Map, map_path: &str) {
+    for (object, position) in layer_objects(map) {
+        let Some(tiled::PropertyValue::StringValue(item)) = object.properties.get("chest") else {
+            continue;
+        };
+        commands.spawn((
+            chest(
+                Chest {
+                    stack: ItemStack {
+                        item: item.clone(),
+                        count: object_count(&object),
+                    },
+                    // Object ids are unique within a map.
+                    opened_flag: format!("chest_opened:{map_path}#{}", object.id()),
+                },
+                position,
+            ),
+            MapObject,
+            ChildOf(map_entity),
+        ));
+    }
+}
+
+/// Spawn a [`Door`] for every object with a `door` property, holding the label
+/// of the marker it leads to. Doors to other maps have a `map` property with
+/// the map's path.
+fn spawn_doors(commands: &mut Commands, map_entity: Entity, map: &tiled::Map) {
+    for (object, position) in layer_objects(map) {
+        let Some(tiled::PropertyValue::StringValue(marker)) = object.properties.get("door") else {
+            continue;
+        };
+        let target_map = match object.properties.get("map") {
+            Some(tiled::PropertyValue::StringValue(path)) => Some(path.clone()),
+            _ => None,
+        };
+        commands.spawn((
+            door(
+                Door {
+                    marker: marker.clone(),
+                    map: target_map,
+                },
+                position,
+            ),
+            MapObject,
+            ChildOf(map_entity),
+        ));
+    }
+}
+
+/// Spawn a [`Sign`] for every object with a `sign` property holding its text.
+/// The sign is named after the object.
+fn spawn_signs(commands: &mut Commands, map_entity: Entity, map: &tiled::Map) {
+    for (object, position) in layer_objects(map) {
+        let Some(tiled::PropertyValue::StringValue(text)) = object.properties.get("sign") else {
+            continue;
+        };
+        commands.spawn((
+            sign(
+                Sign {
+                    name: object.name.clone(),
+                    text: text.clone(),
+                },
+                position,
+            ),
+            MapObject,
+            ChildOf(map_entity),
+        ));
+    }
+}
+
+/// The number in an object's `count` property, or one.
+fn object_count(object: &tiled::Object) -> u32 {
+    match object.properties.get("count") {
+        Some(tiled::PropertyValue::IntValue(count)) => (*count).max(1) as u32,
+        _ => 1,
+    }
+}
+
+/// The items in an object's `drops` property: a comma-separated list of item
+/// ids, each optionally followed by `:count`, e.g. `apple:2, haste_potion`.
+fn object_drops(object: &tiled::Object) -> Vec<ItemStack> {
+    let Some(tiled::PropertyValue::StringValue(drops)) = object.properties.get("drops") else {
+        return Vec::new();
+    };
+    drops
+        .split(',')
+        .map(str::trim)
+        .filter(|drop| !drop.is_empty())
+        .map(|drop| {
+            let (item, count) = drop.split_once(':').unwrap_or((drop, "1"));
+            let count = count.trim().parse().unwrap_or_else(|_| {
+                warn!("Invalid count in drop `{drop}` of object {}", object.name);
+                1
+            });
+            ItemStack {
+                item: item.trim().to_string(),
+                count,
+            }
+        })
+        .collect()
+}
+
+/// Convert a position in Tiled object coordinates into one relative to the map
+/// entity, lining up with the tile layers.
+fn object_position(map: &tiled::Map, x: f32, y: f32) -> Vec2 {
+    let map_type = tilemap_type(map.orientation);
+    let map_size = TilemapSize {
+        x: map.width,
+        y: map.height,
+    };
+    let grid_size = TilemapGridSize {
+        x: map.tile_width as f32,
+        y: map.tile_height as f32,
+    };
+    let tile_size = map.tilesets().first().map_or(
+        TilemapTileSize {
+            x: grid_size.x,
+            y: grid_size.y,
+        },
+        |tileset| TilemapTileSize {
+            x: tileset.tile_width as f32,
+            y: tileset.tile_height as f32,
+        },
+    );
+    let tile_center = |x, y| {
+        TilePos { x, y }.center_in_world(
+            &map_size,
+            &grid_size,
+            &tile_size,
+            &map_type,
+            &TilemapAnchor::Center,
+        )
+    };
+
+    // Tile centers are an affine function of the tile coordinates on square and
+    // isometric maps, so fractional coordinates can be interpolated between them.
+    let origin = tile_center(0, 0);
+    let x_axis = tile_center(1, 0) - origin;
+    let y_axis = tile_center(0, 1) - origin;
+
+    // Isometric maps measure both object axes in tile heights.
+    let column_size = if map.orientation == tiled::Orientation::Orthogonal {
+        grid_size.x
+    } else {
+        grid_size.y
+    };
+    let column = x / column_size - 0.5;
+    // Rows are flipped, like the tile layers are when they are spawned.
+    let row = map.height as f32 - 0.5 - y / grid_size.y;
+
+    origin + x_axis * column + y_axis * row
+}
@@ -0,0 +1,662 @@
+//! Input actions and their bindings.
+//!
+//! Gameplay and menus don't read keys or gamepad buttons directly. Instead,
+//! every [`InputAction`] is bound to any number of keys and gamepad buttons in
+//! the [`InputBindings`] resource, and [`ActionState`] is updated from those
+//! bindings at the start of each frame. Use [`action_just_pressed`] as a run
+//! condition, or read [`ActionState`] in a system. [`LastInputDevice`] tracks
+//! which device the player is using, e.g. to show its bindings in prompts.
+//!
+//! Analog sticks are not bound to actions; read them with [`left_stick`]
+//! and [`right_stick`].
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    time::Duration,
+};
+
+use bevy::{input::InputSystems, prelude::*};
+use serde::{Deserialize, Serialize};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<InputBindings>();
+    app.init_resource::<ActionState>();
+    app.init_resource::<LastInputDevice>();
+    app.add_message::<BindingChanged>();
+    app.add_systems(
+        PreUpdate,
+        (
+            (
+                time_out_pending_rebind,
+                capture_pending_rebind,
+                update_action_state,
+            )
+                .chain(),
+            update_last_input_device,
+        )
+            .after(InputSystems),
+    );
+}
+
+/// Something the player can do, independent of the key or button that does it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Attack,
+    Interact,
+    Inventory,
+    Journal,
+    Pause,
+    Back,
+    NavigateUp,
+    NavigateDown,
+    NavigateLeft,
+    NavigateRight,
+    NavigateNext,
+    Confirm,
+    #[cfg(feature = "dev")]
+    ToggleDebugUi,
+}
+
+impl InputAction {
+    /// Every action, in the order they are listed in the controls menu.
+    pub const ALL: &[Self] = &[
+        Self::MoveUp,
+        Self::MoveDown,
+        Self::MoveLeft,
+        Self::MoveRight,
+        Self::Attack,
+        Self::Interact,
+        Self::Inventory,
+        Self::Journal,
+        Self::Pause,
+        Self::Back,
+        Self::NavigateUp,
+        Self::NavigateDown,
+        Self::NavigateLeft,
+        Self::NavigateRight,
+        Self::NavigateNext,
+        Self::Confirm,
+        #[cfg(feature = "dev")]
+        Self::ToggleDebugUi,
+    ];
+
+    /// A human-readable name for the controls menu.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::MoveUp => "Move Up",
+            Self::MoveDown => "Move Down",
+            Self::MoveLeft => "Move Left",
+            Self::MoveRight => "Move Right",
+            Self::Attack => "Attack",
+            Self::Interact => "Interact",
+            Self::Inventory => "Inventory",
+            Self::Journal => "Journal",
+            Self::Pause => "Pause",
+            Self::Back => "Back",
+            Self::NavigateUp => "Menu Up",
+            Self::NavigateDown => "Menu Down",
+            Self::NavigateLeft => "Menu Left",
+            Self::NavigateRight => "Menu Right",
+            Self::NavigateNext => "Menu Next",
+            Self::Confirm => "Confirm",
+            #[cfg(feature = "dev")]
+            Self::ToggleDebugUi => "Debug UI",
+        }
+    }
+
+    /// Where the action is used. Actions in different contexts can share a binding.
+    pub fn context(&self) -> InputContext {
+        match self {
+            Self::MoveUp
+            | Self::MoveDown
+            | Self::MoveLeft
+            | Self::MoveRight
+            | Self::Attack
+            | Self::Interact
+            | Self::Inventory
+            | Self::Journal
+            | Self::Pause => InputContext::Gameplay,
+            #[cfg(feature = "dev")]
+            Self::ToggleDebugUi => InputContext::Gameplay,
+            Self::Back
+            | Self::NavigateUp
+            | Self::NavigateDown
+            | Self::NavigateLeft
+            | Self::NavigateRight
+            | Self::NavigateNext
+            | Self::Confirm => InputContext::Menu,
+        }
+    }
+
+    /// Whether a single binding can't be shared between the two actions.
+    fn conflicts_with(&self, other: InputAction) -> bool {
+        *self != other && self.context() == other.context()
+    }
+}
+
+/// Where an [`InputAction`] is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum InputContext {
+    /// While playing, with no menu open.
+    Gameplay,
+    /// While a menu is open.
+    Menu,
+}
+
+/// A kind of input device that actions can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum InputDevice {
+    Keyboard,
+    Gamepad,
+}
+
+/// The device the player last pressed a key or button, or moved a stick, on.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct LastInputDevice(pub InputDevice);
+
+impl Default for LastInputDevice {
+    fn default() -> Self {
+        Self(InputDevice::Keyboard)
+    }
+}
+
+/// The keys and gamepad buttons bound to a single [`InputAction`].
+#[derive(Debug, Clone, Default, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionBindings {
+    pub keys: Vec<KeyCode>,
+    pub gamepad_buttons: Vec<GamepadButton>,
+}
+
+impl ActionBindings {
+    fn new(keys: &[KeyCode], gamepad_buttons: &[GamepadButton]) -> Self {
+        Self {
+            keys: keys.to_vec(),
+            gamepad_buttons: gamepad_buttons.to_vec(),
+        }
+    }
+
+    /// A human-readable list of the bindings for the given device.
+    pub fn describe(&self, device: InputDevice) -> String {
+        let names = match device {
+            InputDevice::Keyboard => self.keys.iter().map(key_name).collect::<Vec<_>>(),
+            InputDevice::Gamepad => self
+                .gamepad_buttons
+                .iter()
+                .map(|button| format!("{button:?}"))
+                .collect(),
+        };
+        if names.is_empty() {
+            "-".to_string()
+        } else {
+            names.join(" / ")
+        }
+    }
+}
+
+/// A short name for a key, e.g. `W` instead of `KeyW`.
+fn key_name(key: &KeyCode) -> String {
+    let name = format!("{key:?}");
+    ["Key", "Digit", "Arrow"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .map(str::to_string)
+        .unwrap_or(name)
+}
+
+/// The bindings of every [`InputAction`].
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct InputBindings {
+    actions: HashMap<InputAction, ActionBindings>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use GamepadButton as Pad;
+
+        let actions = InputAction::ALL
+            .iter()
+            .map(|&action| {
+                let bindings = match action {
+                    InputAction::MoveUp => {
+                        ActionBindings::new(&[KeyCode::KeyW, KeyCode::ArrowUp], &[Pad::DPadUp])
+                    }
+                    InputAction::MoveDown => {
+                        ActionBindings::new(&[KeyCode::KeyS, KeyCode::ArrowDown], &[Pad::DPadDown])
+                    }
+                    InputAction::MoveLeft => {
+                        ActionBindings::new(&[KeyCode::KeyA, KeyCode::ArrowLeft], &[Pad::DPadLeft])
+                    }
+                    InputAction::MoveRight => ActionBindings::new(
+                        &[KeyCode::KeyD, KeyCode::ArrowRight],
+                        &[Pad::DPadRight],
+                    ),
+                    InputAction::Attack => ActionBindings::new(&[KeyCode::Space], &[Pad::West]),
+                    InputAction::Interact => ActionBindings::new(&[KeyCode::KeyE], &[Pad::South]),
+                    InputAction::Inventory => ActionBindings::new(&[KeyCode::KeyI], &[Pad::North]),
+                    InputAction::Journal => ActionBindings::new(&[KeyCode::KeyJ], &[Pad::Select]),
+                    InputAction::Pause => {
+                        ActionBindings::new(&[KeyCode::KeyP, KeyCode::Escape], &[Pad::Start])
+                    }
+                    InputAction::Back => ActionBindings::new(&[KeyCode::Escape], &[Pad::East]),
+                    InputAction::NavigateUp => {
+                        ActionBindings::new(&[KeyCode::ArrowUp], &[Pad::DPadUp])
+                    }
+                    InputAction::NavigateDown => {
+                        ActionBindings::new(&[KeyCode::ArrowDown], &[Pad::DPadDown])
+                    }
+                    InputAction::NavigateLeft => {
+                        ActionBindings::new(&[KeyCode::ArrowLeft], &[Pad::DPadLeft])
+                    }
+                    InputAction::NavigateRight => {
+                        ActionBindings::new(&[KeyCode::ArrowRight], &[Pad::DPadRight])
+                    }
+                    InputAction::NavigateNext => {
+                        ActionBindings::new(&[KeyCode::Tab], &[Pad::RightTrigger])
+                    }
+                    InputAction::Confirm => {
+                        ActionBindings::new(&[KeyCode::Enter, KeyCode::Space], &[Pad::South])
+                    }
+                    #[cfg(feature = "dev")]
+                    InputAction::ToggleDebugUi => ActionBindings::new(&[KeyCode::Backquote], &[]),
+                };
+                (action, bindings)
+            })
+            .collect();
+
+        Self { actions }
+    }
+}
+
+impl InputBindings {
+    /// The bindings of a single action.
+    pub fn get(&self, action: InputAction) -> &ActionBindings {
+        &self.actions[&action]
+    }
+
+    /// Bind `key` to `action` in `slot`, replacing the key in that slot or
+    /// adding it after the others if the slot is empty. If another action that
+    /// can't share the key was bound to it, the key is removed from that action,
+    /// which is returned.
+    pub fn bind_key(
+        &mut self,
+        action: InputAction,
+        slot: usize,
+        key: KeyCode,
+    ) -> Option<InputAction> {
+        let conflict = self.unbind_conflicting(action, |bindings| {
+            let len = bindings.keys.len();
+            bindings.keys.retain(|bound| *bound != key);
+            bindings.keys.len() != len
+        });
+        bind_slot(&mut self.actions.get_mut(&action).unwrap().keys, slot, key);
+        conflict
+    }
+
+    /// Bind `button` to `action` in `slot`, like [`Self::bind_key`] does for
+    /// keys.
+    pub fn bind_gamepad_button(
+        &mut self,
+        action: InputAction,
+        slot: usize,
+        button: GamepadButton,
+    ) -> Option<InputAction> {
+        let conflict = self.unbind_conflicting(action, |bindings| {
+            let len = bindings.gamepad_buttons.len();
+            bindings.gamepad_buttons.retain(|bound| *bound != button);
+            bindings.gamepad_buttons.len() != len
+        });
+        let buttons = &mut self.actions.get_mut(&action).unwrap().gamepad_buttons;
+        bind_slot(buttons, slot, button);
+        conflict
+    }
+
+    /// The bindings keyed by action name, for saving to disk.
+    pub fn to_saved(&self) -> BTreeMap<String, ActionBindings> {
+        InputAction::ALL
+            .iter()
+            .map(|action| (format!("{action:?}"), self.get(*action).clone()))
+            .collect()
+    }
+
+    /// Restore bindings saved with [`Self::to_saved`]. Actions missing from
+    /// `saved` keep their default bindings, and unknown action names are
+    /// ignored, so that adding or removing actions doesn't break old files.
+    pub fn from_saved(saved: &BTreeMap<String, ActionBindings>) -> Self {
+        let mut bindings = Self::default();
+        for &action in InputAction::ALL {
+            if let Some(saved) = saved.get(&format!("{action:?}")) {
+                bindings.actions.insert(action, saved.clone());
+            }
+        }
+        bindings
+    }
+
+    /// Run `unbind` on every action that conflicts with `action`, returning the
+    /// first one it reports as changed.
+    fn unbind_conflicting(
+        &mut self,
+        action: InputAction,
+        mut unbind: impl FnMut(&mut ActionBindings) -> bool,
+    ) -> Option<InputAction> {
+        let mut conflict = None;
+        for &other in InputAction::ALL {
+            if action.conflicts_with(other) && unbind(self.actions.get_mut(&other).unwrap()) {
+                conflict.get_or_insert(other);
+            }
+        }
+        conflict
+    }
+}
+
+/// Put `binding` in `slot` of `bound`, or after the other bindings if the slot
+/// is empty, and take it out of any other slot it was in.
+fn bind_slot<T: Copy + PartialEq>(bound: &mut Vec<T>, slot: usize, binding: T) {
+    let slot = match bound.get_mut(slot) {
+        Some(existing) => {
+            *existing = binding;
+            slot
+        }
+        None => {
+            bound.push(binding);
+            bound.len() - 1
+        }
+    };
+    let mut index = 0;
+    bound.retain(|other| {
+        let keep = index == slot || *other != binding;
+        index += 1;
+        keep
+    });
+}
+
+/// Which actions are held down this frame.
+#[derive(Resource, Debug, Default)]
+pub struct ActionState {
+    pressed: HashSet<InputAction>,
+    just_pressed: HashSet<InputAction>,
+}
+
+impl ActionState {
+    /// Whether any binding of `action` is held down.
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// Whether any binding of `action` was pressed this frame.
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+}
+
+/// A run condition that is `true` if `action` was pressed this frame.
+pub fn action_just_pressed(action: InputAction) -> impl FnMut(Res<ActionState>) -> bool + Clone {
+    move |state: Res<ActionState>| state.just_pressed(action)
+}
+
+fn update_action_state(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    bindings: Res<InputBindings>,
+    pending_rebind: Option<Res<PendingRebind>>,
+    mut state: ResMut<ActionState>,
+) {
+    state.pressed.clear();
+    state.just_pressed.clear();
+
+    // Don't trigger actions with the input that is about to become a binding.
+    if pending_rebind.is_some() {
+        return;
+    }
+
+    for (&action, action_bindings) in &bindings.actions {
+        let pressed = keys.any_pressed(action_bindings.keys.iter().copied())
+            || gamepads.iter().any(|gamepad| {
+                gamepad.any_pressed(action_bindings.gamepad_buttons.iter().copied())
+            });
+        let just_pressed = keys.any_just_pressed(action_bindings.keys.iter().copied())
+            || gamepads.iter().any(|gamepad| {
+                gamepad.any_just_pressed(action_bindings.gamepad_buttons.iter().copied())
+            });
+
+        if pressed {
+            state.pressed.insert(action);
+        }
+        if just_pressed {
+            state.just_pressed.insert(action);
+        }
+    }
+}
+
+fn update_last_input_device(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut last_device: ResMut<LastInputDevice>,
+) {
+    if keys.get_just_pressed().next().is_some() {
+        last_device.set_if_neq(LastInputDevice(InputDevice::Keyboard));
+    } else if gamepads.iter().any(|gamepad| {
+        gamepad.get_just_pressed().next().is_some()
+            || apply_deadzone(gamepad.left_stick()) != Vec2::ZERO
+            || apply_deadzone(gamepad.right_stick()) != Vec2::ZERO
+    }) {
+        last_device.set_if_neq(LastInputDevice(InputDevice::Gamepad));
+    }
+}
+
+/// How many bindings of each device the controls menu lets an action have.
+pub const BINDING_SLOTS: usize = 2;
+
+/// How long a [`PendingRebind`] waits for input before it is cancelled.
+pub const REBIND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// While this resource exists, the next key or gamepad button pressed on
+/// `device` becomes the binding of `action` in `slot`, and actions don't
+/// trigger. Any key can be bound, including escape, so the rebind is cancelled
+/// by removing this resource, or by waiting for [`REBIND_TIMEOUT`].
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct PendingRebind {
+    pub action: InputAction,
+    pub device: InputDevice,
+    pub slot: usize,
+}
+
+/// Sent when a [`PendingRebind`] completes.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct BindingChanged {
+    pub action: InputAction,
+    /// The action that lost the binding, if it was already in use.
+    pub conflict: Option<InputAction>,
+}
+
+fn time_out_pending_rebind(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    pending_rebind: Option<Res<PendingRebind>>,
+    mut waited: Local<Duration>,
+) {
+    let Some(pending) = pending_rebind else {
+        return;
+    };
+    // Wait from the start for another slot picked in the meantime.
+    if pending.is_changed() {
+        *waited = Duration::ZERO;
+    }
+    *waited += time.delta();
+    if *waited >= REBIND_TIMEOUT {
+        commands.remove_resource::<PendingRebind>();
+    }
+}
+
+fn capture_pending_rebind(
+    mut commands: Commands,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut gamepads: Query<&mut Gamepad>,
+    pending_rebind: Option<Res<PendingRebind>>,
+    mut bindings: ResMut<InputBindings>,
+    mut binding_changed: MessageWriter<BindingChanged>,
+) {
+    let Some(pending) = pending_rebind else {
+        return;
+    };
+
+    // The captured input is consumed, so that it doesn't also trigger an action
+    // (such as leaving the menu) once the rebind is over.
+    let conflict = match pending.device {
+        InputDevice::Keyboard => {
+            let Some(&key) = keys.get_just_pressed().next() else {
+                return;
+            };
+            keys.clear_just_pressed(key);
+            bindings.bind_key(pending.action, pending.slot, key)
+        }
+        InputDevice::Gamepad => {
+            let Some((mut gamepad, button)) = gamepads.iter_mut().find_map(|gamepad| {
+                let button = gamepad.get_just_pressed().next().copied();
+                button.map(|button| (gamepad, button))
+            }) else {
+                return;
+            };
+            gamepad.digital_mut().clear_just_pressed(button);
+            bindings.bind_gamepad_button(pending.action, pending.slot, button)
+        }
+    };
+
+    commands.remove_resource::<PendingRebind>();
+    binding_changed.write(BindingChanged {
+        action: pending.action,
+        conflict,
+    });
+}
+
+/// Analog stick deflections shorter than this are treated as no input.
+pub const STICK_DEADZONE: f32 = 0.2;
+
+/// Apply a radial deadzone to an analog stick, rescaling the remaining range
+/// so that output magnitude still goes smoothly from `0.0` to `1.0`.
+pub fn apply_deadzone(stick: Vec2) -> Vec2 {
+    let length = stick.length();
+    if length < STICK_DEADZONE {
+        return Vec2::ZERO;
+    }
+
+    let scaled = ((length - STICK_DEADZONE) / (1.0 - STICK_DEADZONE)).min(1.0);
+    stick / length * scaled
+}
+
+/// The deflection of the first gamepad left stick that is outside the deadzone.
+pub fn left_stick(gamepads: &Query<&Gamepad>) -> Vec2 {
+    gamepads
+        .iter()
+        .map(|gamepad| apply_deadzone(gamepad.left_stick()))
+        .find(|stick| *stick != Vec2::ZERO)
+        .unwrap_or(Vec2::ZERO)
+}
+
+/// The deflection of the first gamepad right stick that is outside the deadzone.
+pub fn right_stick(gamepads: &Query<&Gamepad>) -> Vec2 {
+    gamepads
+        .iter()
+        .map(|gamepad| apply_deadzone(gamepad.right_stick()))
+        .find(|stick| *stick != Vec2::ZERO)
+        .unwrap_or(Vec2::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_a_key_takes_it_from_actions_in_the_same_context() {
+        let mut bindings = InputBindings::default();
+        let conflict = bindings.bind_key(InputAction::Attack, 0, KeyCode::KeyP);
+        assert_eq!(conflict, Some(InputAction::Pause));
+        assert_eq!(bindings.get(InputAction::Attack).keys, [KeyCode::KeyP]);
+        assert_eq!(bindings.get(InputAction::Pause).keys, [KeyCode::Escape]);
+    }
+
+    #[test]
+    fn binding_a_key_keeps_it_on_actions_in_other_contexts() {
+        let mut bindings = InputBindings::default();
+        let conflict = bindings.bind_key(InputAction::Back, 1, KeyCode::KeyP);
+        assert_eq!(conflict, None);
+        assert_eq!(
+            bindings.get(InputAction::Back).keys,
+            [KeyCode::Escape, KeyCode::KeyP]
+        );
+        assert_eq!(
+            bindings.get(InputAction::Pause).keys,
+            [KeyCode::KeyP, KeyCode::Escape]
+        );
+    }
+
+    #[test]
+    fn binding_a_gamepad_button_takes_it_from_actions_in_the_same_context() {
+        let mut bindings = InputBindings::default();
+        let conflict =
+            bindings.bind_gamepad_button(InputAction::Attack, 0, GamepadButton::Start);
+        assert_eq!(conflict, Some(InputAction::Pause));
+        assert_eq!(
+            bindings.get(InputAction::Attack).gamepad_buttons,
+            [GamepadButton::Start]
+        );
+        assert!(bindings.get(InputAction::Pause).gamepad_buttons.is_empty());
+    }
+
+    #[test]
+    fn binding_a_slot_replaces_only_that_slot() {
+        let mut bindings = InputBindings::default();
+        bindings.bind_key(InputAction::MoveUp, 1, KeyCode::KeyK);
+        assert_eq!(
+            bindings.get(InputAction::MoveUp).keys,
+            [KeyCode::KeyW, KeyCode::KeyK]
+        );
+
+        // An empty slot is filled after the other bindings.
+        bindings.bind_key(InputAction::MoveUp, BINDING_SLOTS, KeyCode::KeyL);
+        assert_eq!(
+            bindings.get(InputAction::MoveUp).keys,
+            [KeyCode::KeyW, KeyCode::KeyK, KeyCode::KeyL]
+        );
+
+        // A key moved to another slot leaves the one it was in.
+        bindings.bind_key(InputAction::MoveUp, 0, KeyCode::KeyK);
+        assert_eq!(
+            bindings.get(InputAction::MoveUp).keys,
+            [KeyCode::KeyK, KeyCode::KeyL]
+        );
+    }
+
+    #[test]
+    fn saved_bindings_load_back_the_same() {
+        let mut bindings = InputBindings::default();
+        bindings.bind_key(InputAction::Attack, 0, KeyCode::KeyE);
+        bindings.bind_gamepad_button(InputAction::Pause, 1, GamepadButton::Mode);
+
+        let contents = ron::to_string(&bindings.to_saved()).unwrap();
+        let saved = ron::from_str(&contents).unwrap();
+        assert_eq!(InputBindings::from_saved(&saved), bindings);
+    }
+
+    #[test]
+    fn loading_saved_bindings_keeps_defaults_for_missing_actions() {
+        let mut saved = InputBindings::default().to_saved();
+        saved.remove("Attack");
+        saved.insert("Removed".to_string(), ActionBindings::default());
+        saved.insert(
+            "Pause".to_string(),
+            ActionBindings::new(&[KeyCode::KeyF], &[]),
+        );
+
+        let bindings = InputBindings::from_saved(&saved);
+        assert_eq!(bindings.get(InputAction::Attack).keys, [KeyCode::Space]);
+        assert_eq!(bindings.get(InputAction::Pause).keys, [KeyCode::KeyF]);
+    }
+}
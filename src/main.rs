@@ -9,11 +9,18 @@ mod demo;
 #[cfg(feature = "dev")]
 mod dev_tools;
 mod game;
+mod input;
 mod menus;
 mod screens;
+mod settings;
+mod storage;
 mod theme;
 
-use bevy::{asset::AssetMetaCheck, prelude::*};
+use bevy::{
+    asset::AssetMetaCheck,
+    audio::{AudioPlugin, SpatialScale},
+    prelude::*,
+};
 
 fn main() -> AppExit {
     App::new().add_plugins(AppPlugin).run()
@@ -42,6 +49,10 @@ impl Plugin for AppPlugin {
                     .into(),
                     ..default()
                 })
+                .set(AudioPlugin {
+                    default_spatial_scale: SpatialScale::new_2d(1.0 / audio::SPATIAL_AUDIO_UNIT),
+                    ..default()
+                })
                 .set(ImagePlugin::default_nearest()),
         );
 
@@ -51,8 +62,10 @@ impl Plugin for AppPlugin {
             audio::plugin,
             #[cfg(feature = "dev")]
             dev_tools::plugin,
+            input::plugin,
             menus::plugin,
             screens::plugin,
+            settings::plugin,
             theme::plugin,
             game::plugin,
         ));
@@ -99,5 +112,10 @@ struct Pause(pub bool);
 struct PausableSystems;
 
 fn spawn_camera(mut commands: Commands) {
-    commands.spawn((Name::new("Camera"), Camera2d));
+    commands.spawn((
+        Name::new("Camera"),
+        Camera2d,
+        // The camera follows the player, so spatial audio is heard from the player's position.
+        SpatialListener::new(audio::LISTENER_EAR_GAP),
+    ));
 }
@@ -0,0 +1,187 @@
+//! The controls menu, where input actions can be rebound.
+
+use bevy::{ecs::spawn::SpawnWith, prelude::*};
+
+use crate::{
+    input::{
+        BINDING_SLOTS, BindingChanged, InputAction, InputBindings, InputDevice, PendingRebind,
+        action_just_pressed,
+    },
+    menus::Menu,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Controls), spawn_controls_menu);
+    app.add_systems(OnExit(Menu::Controls), cancel_pending_rebind);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::Controls).and(action_just_pressed(InputAction::Back))),
+    );
+
+    app.add_systems(
+        Update,
+        (update_binding_labels, update_conflict_label).run_if(in_state(Menu::Controls)),
+    );
+}
+
+fn spawn_controls_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Controls Menu"),
+        GlobalZIndex(2),
+        DespawnOnExit(Menu::Controls),
+        children![
+            widget::header("Controls"),
+            controls_grid(),
+            (widget::label(""), ConflictLabel),
+            (
+                Name::new("Controls Buttons"),
+                Node {
+                    column_gap: px(20),
+                    ..default()
+                },
+                children![
+                    widget::button_medium("Reset", reset_bindings),
+                    widget::button_medium("Cancel", cancel_pending_rebind_on_click),
+                    widget::button_medium("Back", go_back_on_click),
+                ],
+            ),
+        ],
+    ));
+}
+
+fn controls_grid() -> impl Bundle {
+    (
+        Name::new("Controls Grid"),
+        Node {
+            display: Display::Grid,
+            row_gap: px(4),
+            column_gap: px(20),
+            align_items: AlignItems::Center,
+            grid_template_columns: vec![
+                GridTrack::px(200.0),
+                GridTrack::px(220.0),
+                GridTrack::auto(),
+                GridTrack::px(220.0),
+                GridTrack::auto(),
+            ],
+            ..default()
+        },
+        Children::spawn(SpawnWith(|parent: &mut ChildSpawner| {
+            for &action in InputAction::ALL {
+                parent.spawn((
+                    widget::label(action.label()),
+                    Node {
+                        justify_self: JustifySelf::End,
+                        ..default()
+                    },
+                ));
+                for device in [InputDevice::Keyboard, InputDevice::Gamepad] {
+                    parent.spawn((widget::label(""), BindingLabel { action, device }));
+                    parent.spawn(binding_slot_buttons(action, device));
+                }
+            }
+        })),
+    )
+}
+
+/// A button for each binding slot of an action on one device, rebinding just
+/// that slot so the others are kept.
+fn binding_slot_buttons(action: InputAction, device: InputDevice) -> impl Bundle {
+    (
+        Name::new("Binding Slot Buttons"),
+        Node {
+            column_gap: px(6),
+            ..default()
+        },
+        Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+            for slot in 0..BINDING_SLOTS {
+                parent.spawn(widget::button_small(
+                    (slot + 1).to_string(),
+                    move |_: On<Activate>, mut commands: Commands| {
+                        commands.insert_resource(PendingRebind {
+                            action,
+                            device,
+                            slot,
+                        });
+                    },
+                ));
+            }
+        })),
+    )
+}
+
+/// Shows the current bindings of an action on one device.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct BindingLabel {
+    action: InputAction,
+    device: InputDevice,
+}
+
+fn update_binding_labels(
+    bindings: Res<InputBindings>,
+    pending_rebind: Option<Res<PendingRebind>>,
+    mut label_query: Query<(&BindingLabel, &mut Text)>,
+) {
+    for (label, mut text) in &mut label_query {
+        let pending = pending_rebind.as_ref().is_some_and(|pending| {
+            pending.action == label.action && pending.device == label.device
+        });
+        let content = match (pending, label.device) {
+            (true, InputDevice::Keyboard) => "Press a key...".to_string(),
+            (true, InputDevice::Gamepad) => "Press a button...".to_string(),
+            (false, device) => bindings.get(label.action).describe(device),
+        };
+        if text.0 != content {
+            text.0 = content;
+        }
+    }
+}
+
+/// Reports bindings that were taken away from another action.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ConflictLabel;
+
+fn update_conflict_label(
+    mut binding_changed: MessageReader<BindingChanged>,
+    mut label: Single<&mut Text, With<ConflictLabel>>,
+) {
+    for changed in binding_changed.read() {
+        label.0 = match changed.conflict {
+            Some(conflict) => format!(
+                "Moved the binding from {} to {}",
+                conflict.label(),
+                changed.action.label()
+            ),
+            None => String::new(),
+        };
+    }
+}
+
+fn reset_bindings(
+    _: On<Activate>,
+    mut bindings: ResMut<InputBindings>,
+    mut label: Single<&mut Text, With<ConflictLabel>>,
+) {
+    *bindings = InputBindings::default();
+    label.0.clear();
+}
+
+fn cancel_pending_rebind(mut commands: Commands) {
+    commands.remove_resource::<PendingRebind>();
+}
+
+/// Stop waiting for a binding. Every key can be bound, so there is no key for this.
+fn cancel_pending_rebind_on_click(_: On<Activate>, mut commands: Commands) {
+    commands.remove_resource::<PendingRebind>();
+}
+
+fn go_back_on_click(_: On<Activate>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
@@ -1,18 +1,25 @@
 //! The credits menu.
 
-use bevy::{ecs::spawn::SpawnIter, input::common_conditions::input_just_pressed, prelude::*};
+use bevy::{ecs::spawn::SpawnIter, prelude::*};
 
-use crate::{asset_tracking::LoadResource, audio::music, menus::Menu, theme::prelude::*};
+use crate::{
+    asset_tracking::LoadResource,
+    audio::director::{MusicDirector, Playlist},
+    input::{InputAction, action_just_pressed},
+    menus::Menu,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Credits), spawn_credits_menu);
     app.add_systems(
         Update,
-        go_back.run_if(in_state(Menu::Credits).and(input_just_pressed(KeyCode::Escape))),
+        go_back.run_if(in_state(Menu::Credits).and(action_just_pressed(InputAction::Back))),
     );
 
     app.load_resource::<CreditsAssets>();
     app.add_systems(OnEnter(Menu::Credits), start_credits_music);
+    app.add_systems(OnExit(Menu::Credits), stop_credits_music);
 }
 
 fn spawn_credits_menu(mut commands: Commands) {
@@ -77,7 +84,7 @@ fn grid(content: Vec<[&'static str; 2]>) -> impl Bundle {
     )
 }
 
-fn go_back_on_click(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+fn go_back_on_click(_: On<Activate>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Main);
 }
 
@@ -101,10 +108,10 @@ impl FromWorld for CreditsAssets {
     }
 }
 
-fn start_credits_music(mut commands: Commands, credits_music: Res<CreditsAssets>) {
-    commands.spawn((
-        Name::new("Credits Music"),
-        DespawnOnExit(Menu::Credits),
-        music(credits_music.music.clone()),
-    ));
+fn start_credits_music(mut director: ResMut<MusicDirector>, credits_music: Res<CreditsAssets>) {
+    director.play(Playlist::single(credits_music.music.clone()));
+}
+
+fn stop_credits_music(mut director: ResMut<MusicDirector>) {
+    director.stop();
 }
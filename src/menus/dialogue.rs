@@ -0,0 +1,202 @@
+//! The dialogue box, which shows the conversation with an NPC a line at a time.
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{
+        dialogue::{ActiveDialogue, DialogueScript, GoToDialogueNode},
+        flags::GameFlags,
+    },
+    input::{InputAction, action_just_pressed},
+    menus::Menu,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Dialogue), spawn_dialogue_box);
+    app.add_systems(OnExit(Menu::Dialogue), end_dialogue);
+    app.add_systems(
+        Update,
+        (
+            advance_dialogue.run_if(action_just_pressed(InputAction::Confirm)),
+            close_dialogue.run_if(action_just_pressed(InputAction::Back)),
+            (reveal_text, update_dialogue_box).chain(),
+        )
+            .run_if(in_state(Menu::Dialogue).and(resource_exists::<ActiveDialogue>)),
+    );
+}
+
+/// How quickly lines are typed out.
+const REVEAL_CHARS_PER_SECOND: f32 = 40.0;
+
+fn spawn_dialogue_box(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Dialogue Menu"),
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            height: percent(100),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::End,
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(px(20)),
+            ..default()
+        },
+        Pickable::IGNORE,
+        GlobalZIndex(2),
+        DespawnOnExit(Menu::Dialogue),
+        children![(
+            Name::new("Dialogue Box"),
+            Node {
+                width: px(720),
+                min_height: px(160),
+                flex_direction: FlexDirection::Column,
+                row_gap: px(10),
+                padding: UiRect::all(px(20)),
+                ..default()
+            },
+            BorderRadius::all(px(12)),
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.9)),
+            children![
+                (widget::label(""), DialogueSpeaker),
+                (
+                    Name::new("Dialogue Text"),
+                    DialogueText,
+                    Text::default(),
+                    TextFont::from_font_size(28.0),
+                    TextColor(ui_palette::HEADER_TEXT),
+                ),
+                (
+                    Name::new("Dialogue Choices"),
+                    DialogueChoices::default(),
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: px(6),
+                        ..default()
+                    },
+                ),
+            ],
+        )],
+    ));
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct DialogueSpeaker;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct DialogueText;
+
+/// Holds a button for each choice, once the last line of a node is shown.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+struct DialogueChoices {
+    /// The node the buttons were made for.
+    node: Option<String>,
+}
+
+fn reveal_text(
+    time: Res<Time>,
+    scripts: Res<Assets<DialogueScript>>,
+    mut dialogue: ResMut<ActiveDialogue>,
+) {
+    let Some(line) = dialogue.current_line(&scripts) else {
+        return;
+    };
+    let length = line.chars().count() as f32;
+    if dialogue.revealed < length {
+        dialogue.revealed = (dialogue.revealed + REVEAL_CHARS_PER_SECOND * time.delta_secs())
+            .min(length);
+    }
+}
+
+fn update_dialogue_box(
+    mut commands: Commands,
+    scripts: Res<Assets<DialogueScript>>,
+    flags: Res<GameFlags>,
+    dialogue: Res<ActiveDialogue>,
+    mut speaker: Single<&mut Text, With<DialogueSpeaker>>,
+    mut text: Single<&mut Text, (With<DialogueText>, Without<DialogueSpeaker>)>,
+    choices: Single<(Entity, &mut DialogueChoices)>,
+) {
+    if !dialogue.is_changed() {
+        return;
+    }
+    let Some(node) = dialogue.current_node(&scripts) else {
+        return;
+    };
+    let Some(line) = node.lines.get(dialogue.line) else {
+        return;
+    };
+
+    let name = node.speaker.as_ref().unwrap_or(&dialogue.speaker);
+    if speaker.0 != *name {
+        speaker.0 = name.clone();
+    }
+    text.0 = line.chars().take(dialogue.revealed as usize).collect();
+
+    // Offer the choices once the last line has been typed out.
+    let line_finished = dialogue.revealed as usize >= line.chars().count();
+    let show_choices = line_finished && dialogue.line + 1 == node.lines.len();
+    let choices_node = show_choices.then(|| dialogue.node.clone());
+    let (choices_entity, mut choices) = choices.into_inner();
+    if choices.node == choices_node {
+        return;
+    }
+    choices.node = choices_node;
+
+    let mut choices_entity = commands.entity(choices_entity);
+    choices_entity.despawn_related::<Children>();
+    if !show_choices {
+        return;
+    }
+    choices_entity.with_children(|parent| {
+        for choice in node.available_choices(&flags) {
+            let next = choice.next.clone();
+            parent.spawn(widget::button_wide(
+                choice.text.clone(),
+                move |_: On<Activate>, mut commands: Commands| {
+                    commands.trigger(GoToDialogueNode { node: next.clone() });
+                },
+            ));
+        }
+    });
+}
+
+/// Type out the rest of the line, move on to the next line, or move on to the
+/// next node if there are no choices to pick from.
+fn advance_dialogue(
+    mut commands: Commands,
+    scripts: Res<Assets<DialogueScript>>,
+    flags: Res<GameFlags>,
+    mut dialogue: ResMut<ActiveDialogue>,
+) {
+    let Some(node) = dialogue.current_node(&scripts) else {
+        return;
+    };
+    let Some(line) = node.lines.get(dialogue.line) else {
+        return;
+    };
+
+    let length = line.chars().count() as f32;
+    if dialogue.revealed < length {
+        dialogue.revealed = length;
+    } else if dialogue.line + 1 < node.lines.len() {
+        dialogue.line += 1;
+        dialogue.revealed = 0.0;
+    } else if node.available_choices(&flags).next().is_none() {
+        commands.trigger(GoToDialogueNode {
+            node: node.next.clone(),
+        });
+    }
+}
+
+fn close_dialogue(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::None);
+}
+
+fn end_dialogue(mut commands: Commands) {
+    commands.remove_resource::<ActiveDialogue>();
+}
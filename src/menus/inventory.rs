@@ -0,0 +1,191 @@
+//! The inventory menu, where the player uses, equips and drops items.
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{
+        inventory::{DropItem, EquipItem, Inventory, UnequipItem, UseItem},
+        items::{EquipmentSlot, Items},
+        player::Player,
+    },
+    input::{InputAction, action_just_pressed},
+    menus::Menu,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Inventory), spawn_inventory_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(
+            in_state(Menu::Inventory).and(
+                action_just_pressed(InputAction::Back)
+                    .or(action_just_pressed(InputAction::Inventory)),
+            ),
+        ),
+    );
+
+    app.add_systems(Update, update_inventory_grid.run_if(in_state(Menu::Inventory)));
+}
+
+fn spawn_inventory_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Inventory Menu"),
+        GlobalZIndex(2),
+        DespawnOnExit(Menu::Inventory),
+        children![
+            widget::header("Inventory"),
+            (
+                Name::new("Inventory Grid"),
+                InventoryGrid,
+                Node {
+                    display: Display::Grid,
+                    row_gap: px(4),
+                    column_gap: px(20),
+                    align_items: AlignItems::Center,
+                    grid_template_columns: vec![
+                        GridTrack::px(240.0),
+                        GridTrack::px(420.0),
+                        GridTrack::auto(),
+                        GridTrack::auto(),
+                    ],
+                    ..default()
+                },
+            ),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+/// Lists the player's equipped items and item stacks, with buttons to act on
+/// them.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct InventoryGrid;
+
+/// Fill the grid when the menu opens, and again whenever the inventory changes.
+fn update_inventory_grid(
+    mut commands: Commands,
+    items: Items,
+    player: Single<(Entity, Ref<Inventory>), With<Player>>,
+    grid: Single<(Entity, Ref<InventoryGrid>)>,
+) {
+    let (player, inventory) = player.into_inner();
+    let (grid, grid_marker) = grid.into_inner();
+    if !inventory.is_changed() && !grid_marker.is_added() {
+        return;
+    }
+
+    let mut rows = Vec::new();
+    for (&slot, item) in inventory.equipped() {
+        let definition = items.get(item);
+        rows.push(InventoryRow {
+            name: format!("{} ({slot:?})", definition.map_or(item, |item| &item.name)),
+            description: definition.map(|item| item.description.clone()).unwrap_or_default(),
+            action: Some(ItemAction::Unequip(slot)),
+            drop: None,
+        });
+    }
+    for (index, stack) in inventory.stacks().iter().enumerate() {
+        let definition = items.get(&stack.item);
+        let name = definition.map_or(&stack.item, |item| &item.name);
+        let action = definition.and_then(|item| {
+            if item.equip.is_some() {
+                Some(ItemAction::Equip(index))
+            } else if item.consume.is_some() {
+                Some(ItemAction::Use(index))
+            } else {
+                None
+            }
+        });
+        rows.push(InventoryRow {
+            name: if stack.count > 1 {
+                format!("{name} x{}", stack.count)
+            } else {
+                name.clone()
+            },
+            description: definition.map(|item| item.description.clone()).unwrap_or_default(),
+            action,
+            drop: Some(index),
+        });
+    }
+
+    let mut grid = commands.entity(grid);
+    grid.despawn_related::<Children>();
+    if rows.is_empty() {
+        grid.with_child(widget::label("Nothing here yet."));
+        return;
+    }
+    grid.with_children(|parent| {
+        for row in rows {
+            parent.spawn(widget::label(row.name));
+            parent.spawn(widget::label_small(row.description));
+            match row.action {
+                Some(action) => parent.spawn(widget::button_medium(
+                    action.label(),
+                    move |_: On<Activate>, mut commands: Commands| {
+                        action.trigger(&mut commands, player);
+                    },
+                )),
+                None => parent.spawn(widget::label("")),
+            };
+            match row.drop {
+                Some(index) => parent.spawn(widget::button_medium(
+                    "Drop",
+                    move |_: On<Activate>, mut commands: Commands| {
+                        commands.trigger(DropItem {
+                            entity: player,
+                            index,
+                        });
+                    },
+                )),
+                None => parent.spawn(widget::label("")),
+            };
+        }
+    });
+}
+
+/// A line of the inventory grid.
+struct InventoryRow {
+    name: String,
+    description: String,
+    action: Option<ItemAction>,
+    /// The index of the stack to drop, for items that aren't equipped.
+    drop: Option<usize>,
+}
+
+/// What the action button of an [`InventoryRow`] does.
+#[derive(Clone, Copy)]
+enum ItemAction {
+    /// Use an item from the stack at an index.
+    Use(usize),
+    /// Equip an item from the stack at an index.
+    Equip(usize),
+    Unequip(EquipmentSlot),
+}
+
+impl ItemAction {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Use(_) => "Use",
+            Self::Equip(_) => "Equip",
+            Self::Unequip(_) => "Unequip",
+        }
+    }
+
+    fn trigger(self, commands: &mut Commands, entity: Entity) {
+        match self {
+            Self::Use(index) => commands.trigger(UseItem { entity, index }),
+            Self::Equip(index) => commands.trigger(EquipItem { entity, index }),
+            Self::Unequip(slot) => commands.trigger(UnequipItem { entity, slot }),
+        }
+    }
+}
+
+fn go_back_on_click(_: On<Activate>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::None);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::None);
+}
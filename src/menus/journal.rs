@@ -0,0 +1,100 @@
+//! The quest journal, listing the quests in progress and the completed ones.
+
+use bevy::prelude::*;
+
+use crate::{
+    game::quests::{QuestLog, Quests},
+    input::{InputAction, action_just_pressed},
+    menus::Menu,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Journal), spawn_journal_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(
+            in_state(Menu::Journal).and(
+                action_just_pressed(InputAction::Back)
+                    .or(action_just_pressed(InputAction::Journal)),
+            ),
+        ),
+    );
+
+    app.add_systems(Update, update_quest_list.run_if(in_state(Menu::Journal)));
+}
+
+fn spawn_journal_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Journal Menu"),
+        GlobalZIndex(2),
+        DespawnOnExit(Menu::Journal),
+        children![
+            widget::header("Journal"),
+            (
+                Name::new("Quest List"),
+                QuestList,
+                Node {
+                    width: px(720),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(6),
+                    ..default()
+                },
+            ),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+/// Lists the quests in the [`QuestLog`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct QuestList;
+
+/// Fill the list when the menu opens, and again whenever the quest log changes.
+fn update_quest_list(
+    mut commands: Commands,
+    quests: Quests,
+    quest_log: Res<QuestLog>,
+    list: Single<(Entity, Ref<QuestList>)>,
+) {
+    let (list, list_marker) = list.into_inner();
+    if !quest_log.is_changed() && !list_marker.is_added() {
+        return;
+    }
+
+    let mut list = commands.entity(list);
+    list.despawn_related::<Children>();
+    if quest_log.active().is_empty() && quest_log.completed().is_empty() {
+        list.with_child(widget::label("No quests yet."));
+        return;
+    }
+    list.with_children(|parent| {
+        for (id, progress) in quest_log.active() {
+            let Some(quest) = quests.get(id) else {
+                continue;
+            };
+            parent.spawn(widget::label(quest.name.clone()));
+            parent.spawn(widget::label_small(quest.description.clone()));
+            for (objective, &progress) in quest.objectives.iter().zip(progress) {
+                let mark = if progress >= objective.target() { "[x]" } else { "[ ]" };
+                parent.spawn(widget::label_small(format!(
+                    "{mark} {}",
+                    objective.describe(progress)
+                )));
+            }
+        }
+        for id in quest_log.completed() {
+            let name = quests.get(id).map_or(id, |quest| &quest.name);
+            parent.spawn(widget::label_small(format!("{name} (completed)")));
+        }
+    });
+}
+
+fn go_back_on_click(_: On<Activate>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::None);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::None);
+}
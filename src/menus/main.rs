@@ -2,7 +2,13 @@
 
 use bevy::prelude::*;
 
-use crate::{asset_tracking::ResourceHandles, menus::Menu, screens::Screen, theme::widget};
+use crate::{
+    asset_tracking::ResourceHandles,
+    game::save_game::NewGame,
+    menus::Menu,
+    screens::Screen,
+    theme::{navigation::Activate, widget},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Main), spawn_main_menu);
@@ -15,14 +21,16 @@ fn spawn_main_menu(mut commands: Commands) {
         DespawnOnExit(Menu::Main),
         #[cfg(not(target_family = "wasm"))]
         children![
-            widget::button("Play", enter_loading_or_gameplay_screen),
+            widget::button("Continue", enter_loading_or_gameplay_screen),
+            widget::button("New Game", start_new_game),
             widget::button("Settings", open_settings_menu),
             widget::button("Credits", open_credits_menu),
             widget::button("Exit", exit_app),
         ],
         #[cfg(target_family = "wasm")]
         children![
-            widget::button("Play", enter_loading_or_gameplay_screen),
+            widget::button("Continue", enter_loading_or_gameplay_screen),
+            widget::button("New Game", start_new_game),
             widget::button("Settings", open_settings_menu),
             widget::button("Credits", open_credits_menu),
         ],
@@ -30,7 +38,7 @@ fn spawn_main_menu(mut commands: Commands) {
 }
 
 fn enter_loading_or_gameplay_screen(
-    _: On<Pointer<Click>>,
+    _: On<Activate>,
     resource_handles: Res<ResourceHandles>,
     mut next_screen: ResMut<NextState<Screen>>,
 ) {
@@ -41,15 +49,29 @@ fn enter_loading_or_gameplay_screen(
     }
 }
 
-fn open_settings_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+fn start_new_game(
+    _: On<Activate>,
+    mut commands: Commands,
+    resource_handles: Res<ResourceHandles>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    commands.trigger(NewGame);
+    if resource_handles.is_all_done() {
+        next_screen.set(Screen::Gameplay);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
+}
+
+fn open_settings_menu(_: On<Activate>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Settings);
 }
 
-fn open_credits_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+fn open_credits_menu(_: On<Activate>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Credits);
 }
 
 #[cfg(not(target_family = "wasm"))]
-fn exit_app(_: On<Pointer<Click>>, mut app_exit: MessageWriter<AppExit>) {
+fn exit_app(_: On<Activate>, mut app_exit: MessageWriter<AppExit>) {
     app_exit.write(AppExit::Success);
 }
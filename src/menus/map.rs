@@ -0,0 +1,50 @@
+//! The map menu, which shows the minimap full screen.
+
+use bevy::prelude::*;
+
+use crate::{
+    input::{InputAction, action_just_pressed},
+    menus::Menu,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Map), spawn_map_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::Map).and(action_just_pressed(InputAction::Back))),
+    );
+}
+
+fn spawn_map_menu(mut commands: Commands) {
+    // The map itself is the minimap, which grows to fill the screen behind the
+    // header and the back button.
+    commands.spawn((
+        Name::new("Map Menu"),
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            height: percent(100),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::SpaceBetween,
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(px(20)),
+            ..default()
+        },
+        Pickable::IGNORE,
+        GlobalZIndex(3),
+        DespawnOnExit(Menu::Map),
+        children![
+            widget::header("Map"),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn go_back_on_click(_: On<Activate>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Pause);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Pause);
+}
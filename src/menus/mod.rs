@@ -1,7 +1,12 @@
 //! The game's menus and transitions between them.
 
+mod controls;
 mod credits;
+mod dialogue;
+mod inventory;
+mod journal;
 mod main;
+mod map;
 mod pause;
 mod settings;
 
@@ -11,8 +16,13 @@ pub(super) fn plugin(app: &mut App) {
     app.init_state::<Menu>();
 
     app.add_plugins((
+        controls::plugin,
         credits::plugin,
+        dialogue::plugin,
+        inventory::plugin,
+        journal::plugin,
         main::plugin,
+        map::plugin,
         settings::plugin,
         pause::plugin,
     ));
@@ -25,5 +35,10 @@ pub enum Menu {
     Main,
     Credits,
     Settings,
+    Controls,
     Pause,
+    Map,
+    Inventory,
+    Journal,
+    Dialogue,
 }
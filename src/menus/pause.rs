@@ -1,14 +1,19 @@
 //! The pause menu.
 
-use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use bevy::prelude::*;
 
-use crate::{menus::Menu, screens::Screen, theme::widget};
+use crate::{
+    input::{InputAction, action_just_pressed},
+    menus::Menu,
+    screens::Screen,
+    theme::{navigation::Activate, widget},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Pause), spawn_pause_menu);
     app.add_systems(
         Update,
-        go_back.run_if(in_state(Menu::Pause).and(input_just_pressed(KeyCode::Escape))),
+        go_back.run_if(in_state(Menu::Pause).and(action_just_pressed(InputAction::Back))),
     );
 }
 
@@ -20,21 +25,26 @@ fn spawn_pause_menu(mut commands: Commands) {
         children![
             widget::header("Game paused"),
             widget::button("Continue", close_menu),
+            widget::button("Map", open_map_menu),
             widget::button("Settings", open_settings_menu),
             widget::button("Quit to title", quit_to_title),
         ],
     ));
 }
 
-fn open_settings_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+fn open_map_menu(_: On<Activate>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Map);
+}
+
+fn open_settings_menu(_: On<Activate>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Settings);
 }
 
-fn close_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+fn close_menu(_: On<Activate>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::None);
 }
 
-fn quit_to_title(_: On<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+fn quit_to_title(_: On<Activate>, mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Title);
 }
 
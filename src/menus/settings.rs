@@ -2,20 +2,38 @@
 //!
 //! Additional settings and accessibility options should go here.
 
-use bevy::{audio::Volume, input::common_conditions::input_just_pressed, prelude::*};
+use bevy::{audio::Volume, prelude::*};
 
-use crate::{menus::Menu, screens::Screen, theme::prelude::*};
+use crate::{
+    audio::{AudioChannel, ChannelVolumes},
+    game::{
+        camera::CameraSmoothing, camera_shake::ReducedMotion, pixel_perfect::PixelPerfect,
+        player::MovementInputMode,
+    },
+    input::{InputAction, action_just_pressed},
+    menus::Menu,
+    screens::Screen,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Settings), spawn_settings_menu);
     app.add_systems(
         Update,
-        go_back.run_if(in_state(Menu::Settings).and(input_just_pressed(KeyCode::Escape))),
+        go_back.run_if(in_state(Menu::Settings).and(action_just_pressed(InputAction::Back))),
     );
 
     app.add_systems(
         Update,
-        update_global_volume_label.run_if(in_state(Menu::Settings)),
+        (
+            update_global_volume_label,
+            update_channel_volume_labels,
+            update_movement_input_mode_label,
+            update_camera_smoothing_label,
+            update_reduced_motion_label,
+            update_pixel_perfect_label,
+        )
+            .run_if(in_state(Menu::Settings)),
     );
 }
 
@@ -27,6 +45,7 @@ fn spawn_settings_menu(mut commands: Commands) {
         children![
             widget::header("Settings"),
             settings_grid(),
+            widget::button("Controls", open_controls_menu),
             widget::button("Back", go_back_on_click),
         ],
     ));
@@ -43,18 +62,39 @@ fn settings_grid() -> impl Bundle {
             ..default()
         },
         children![
-            (
-                widget::label("Master Volume"),
-                Node {
-                    justify_self: JustifySelf::End,
-                    ..default()
-                }
-            ),
+            setting_label("Master Volume"),
             global_volume_widget(),
+            setting_label(AudioChannel::Music.label()),
+            channel_volume_widget(AudioChannel::Music),
+            setting_label(AudioChannel::SoundEffect.label()),
+            channel_volume_widget(AudioChannel::SoundEffect),
+            setting_label(AudioChannel::Ui.label()),
+            channel_volume_widget(AudioChannel::Ui),
+            setting_label(AudioChannel::Ambient.label()),
+            channel_volume_widget(AudioChannel::Ambient),
+            setting_label("Movement Input"),
+            movement_input_mode_widget(),
+            setting_label("Camera Smoothing"),
+            camera_smoothing_widget(),
+            setting_label("Reduced Motion"),
+            reduced_motion_widget(),
+            setting_label("Pixel Perfect"),
+            pixel_perfect_widget(),
         ],
     )
 }
 
+/// The name of a setting, in the left column of the settings grid.
+fn setting_label(text: &'static str) -> impl Bundle {
+    (
+        widget::label(text),
+        Node {
+            justify_self: JustifySelf::End,
+            ..default()
+        },
+    )
+}
+
 fn global_volume_widget() -> impl Bundle {
     (
         Name::new("Global Volume Widget"),
@@ -81,12 +121,12 @@ fn global_volume_widget() -> impl Bundle {
 const MIN_VOLUME: f32 = 0.0;
 const MAX_VOLUME: f32 = 3.0;
 
-fn lower_global_volume(_: On<Pointer<Click>>, mut global_volume: ResMut<GlobalVolume>) {
+fn lower_global_volume(_: On<Activate>, mut global_volume: ResMut<GlobalVolume>) {
     let linear = (global_volume.volume.to_linear() - 0.1).max(MIN_VOLUME);
     global_volume.volume = Volume::Linear(linear);
 }
 
-fn raise_global_volume(_: On<Pointer<Click>>, mut global_volume: ResMut<GlobalVolume>) {
+fn raise_global_volume(_: On<Activate>, mut global_volume: ResMut<GlobalVolume>) {
     let linear = (global_volume.volume.to_linear() + 0.1).min(MAX_VOLUME);
     global_volume.volume = Volume::Linear(linear);
 }
@@ -103,8 +143,240 @@ fn update_global_volume_label(
     label.0 = format!("{percent:3.0}%");
 }
 
+fn channel_volume_widget(channel: AudioChannel) -> impl Bundle {
+    (
+        Name::new(format!("{} Volume Widget", channel.label())),
+        Node {
+            justify_self: JustifySelf::Start,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        children![
+            widget::button_small(
+                "-",
+                move |_: On<Activate>, mut volumes: ResMut<ChannelVolumes>| {
+                    let volume = &mut volumes.get_mut(channel).volume;
+                    *volume = (*volume - 0.1).max(MIN_VOLUME);
+                },
+            ),
+            (
+                Name::new("Current Volume"),
+                Node {
+                    padding: UiRect::horizontal(px(10)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), ChannelVolumeLabel(channel))],
+            ),
+            widget::button_small(
+                "+",
+                move |_: On<Activate>, mut volumes: ResMut<ChannelVolumes>| {
+                    let volume = &mut volumes.get_mut(channel).volume;
+                    *volume = (*volume + 0.1).min(MAX_CHANNEL_VOLUME);
+                },
+            ),
+            (
+                Node {
+                    margin: UiRect::left(px(10)),
+                    ..default()
+                },
+                children![widget::button_medium(
+                    "Mute",
+                    move |_: On<Activate>, mut volumes: ResMut<ChannelVolumes>| {
+                        let muted = &mut volumes.get_mut(channel).muted;
+                        *muted = !*muted;
+                    },
+                )],
+            ),
+        ],
+    )
+}
+
+/// Channel volumes are relative to the master volume, so they don't go above 100%.
+const MAX_CHANNEL_VOLUME: f32 = 1.0;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ChannelVolumeLabel(AudioChannel);
+
+fn update_channel_volume_labels(
+    volumes: Res<ChannelVolumes>,
+    mut labels: Query<(&ChannelVolumeLabel, &mut Text)>,
+) {
+    for (label, mut text) in &mut labels {
+        let volume = volumes.get(label.0);
+        text.0 = if volume.muted {
+            "Muted".to_string()
+        } else {
+            format!("{:3.0}%", 100.0 * volume.volume)
+        };
+    }
+}
+
+fn movement_input_mode_widget() -> impl Bundle {
+    (
+        Name::new("Movement Input Mode Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_movement_input_mode),
+            (
+                Name::new("Current Movement Input Mode"),
+                Node {
+                    padding: UiRect::horizontal(px(10)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), MovementInputModeLabel)],
+            ),
+            widget::button_small(">", toggle_movement_input_mode),
+        ],
+    )
+}
+
+fn toggle_movement_input_mode(_: On<Activate>, mut input_mode: ResMut<MovementInputMode>) {
+    input_mode.toggle();
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct MovementInputModeLabel;
+
+fn update_movement_input_mode_label(
+    input_mode: Res<MovementInputMode>,
+    mut label: Single<&mut Text, With<MovementInputModeLabel>>,
+) {
+    label.0 = input_mode.label().to_string();
+}
+
+fn camera_smoothing_widget() -> impl Bundle {
+    (
+        Name::new("Camera Smoothing Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small(
+                "<",
+                |_: On<Activate>, mut smoothing: ResMut<CameraSmoothing>| {
+                    *smoothing = smoothing.previous();
+                },
+            ),
+            (
+                Name::new("Current Camera Smoothing"),
+                Node {
+                    padding: UiRect::horizontal(px(10)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), CameraSmoothingLabel)],
+            ),
+            widget::button_small(
+                ">",
+                |_: On<Activate>, mut smoothing: ResMut<CameraSmoothing>| {
+                    *smoothing = smoothing.next();
+                },
+            ),
+        ],
+    )
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct CameraSmoothingLabel;
+
+fn update_camera_smoothing_label(
+    smoothing: Res<CameraSmoothing>,
+    mut label: Single<&mut Text, With<CameraSmoothingLabel>>,
+) {
+    label.0 = smoothing.label().to_string();
+}
+
+fn reduced_motion_widget() -> impl Bundle {
+    (
+        Name::new("Reduced Motion Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_reduced_motion),
+            (
+                Name::new("Current Reduced Motion"),
+                Node {
+                    padding: UiRect::horizontal(px(10)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), ReducedMotionLabel)],
+            ),
+            widget::button_small(">", toggle_reduced_motion),
+        ],
+    )
+}
+
+fn toggle_reduced_motion(_: On<Activate>, mut reduced_motion: ResMut<ReducedMotion>) {
+    reduced_motion.0 = !reduced_motion.0;
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ReducedMotionLabel;
+
+fn update_reduced_motion_label(
+    reduced_motion: Res<ReducedMotion>,
+    mut label: Single<&mut Text, With<ReducedMotionLabel>>,
+) {
+    label.0 = if reduced_motion.0 { "On" } else { "Off" }.to_string();
+}
+
+fn pixel_perfect_widget() -> impl Bundle {
+    (
+        Name::new("Pixel Perfect Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_pixel_perfect),
+            (
+                Name::new("Current Pixel Perfect"),
+                Node {
+                    padding: UiRect::horizontal(px(10)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), PixelPerfectLabel)],
+            ),
+            widget::button_small(">", toggle_pixel_perfect),
+        ],
+    )
+}
+
+fn toggle_pixel_perfect(_: On<Activate>, mut pixel_perfect: ResMut<PixelPerfect>) {
+    pixel_perfect.0 = !pixel_perfect.0;
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct PixelPerfectLabel;
+
+fn update_pixel_perfect_label(
+    pixel_perfect: Res<PixelPerfect>,
+    mut label: Single<&mut Text, With<PixelPerfectLabel>>,
+) {
+    label.0 = if pixel_perfect.0 { "On" } else { "Off" }.to_string();
+}
+
+fn open_controls_menu(_: On<Activate>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Controls);
+}
+
 fn go_back_on_click(
-    _: On<Pointer<Click>>,
+    _: On<Activate>,
     screen: Res<State<Screen>>,
     mut next_menu: ResMut<NextState<Menu>>,
 ) {
@@ -1,25 +1,44 @@
 //! The screen state for the main gameplay.
 
-use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use bevy::prelude::*;
 
-use crate::{Pause, game::level::spawn_level, menus::Menu, screens::Screen};
+use crate::{
+    Pause,
+    audio::director::{DuckReason, MusicDirector},
+    game::level::spawn_level,
+    input::{InputAction, action_just_pressed},
+    menus::Menu,
+    screens::Screen,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Gameplay), spawn_level);
 
-    // Toggle pause on key press.
+    // Toggle pause on key press, or pause to open the inventory or journal.
     app.add_systems(
         Update,
         (
             (pause, spawn_pause_overlay, open_pause_menu).run_if(
                 in_state(Screen::Gameplay)
                     .and(in_state(Menu::None))
-                    .and(input_just_pressed(KeyCode::KeyP).or(input_just_pressed(KeyCode::Escape))),
+                    .and(action_just_pressed(InputAction::Pause)),
+            ),
+            (pause, spawn_pause_overlay, open_inventory_menu).run_if(
+                in_state(Screen::Gameplay)
+                    .and(in_state(Menu::None))
+                    .and(action_just_pressed(InputAction::Inventory)),
+            ),
+            (pause, spawn_pause_overlay, open_journal_menu).run_if(
+                in_state(Screen::Gameplay)
+                    .and(in_state(Menu::None))
+                    .and(action_just_pressed(InputAction::Journal)),
             ),
             close_menu.run_if(
                 in_state(Screen::Gameplay)
                     .and(not(in_state(Menu::None)))
-                    .and(input_just_pressed(KeyCode::KeyP)),
+                    .and(action_just_pressed(InputAction::Pause))
+                    // Bindings shared with `Back` are handled by the open menu itself.
+                    .and(not(action_just_pressed(InputAction::Back))),
             ),
         ),
     );
@@ -28,6 +47,18 @@ pub(super) fn plugin(app: &mut App) {
         OnEnter(Menu::None),
         unpause.run_if(in_state(Screen::Gameplay)),
     );
+
+    // Duck the music while paused.
+    app.add_systems(OnEnter(Pause(true)), duck_music);
+    app.add_systems(OnExit(Pause(true)), unduck_music);
+}
+
+fn duck_music(mut music_director: ResMut<MusicDirector>) {
+    music_director.duck(DuckReason::Pause);
+}
+
+fn unduck_music(mut music_director: ResMut<MusicDirector>) {
+    music_director.unduck(DuckReason::Pause);
 }
 
 fn unpause(mut next_pause: ResMut<NextState<Pause>>) {
@@ -56,6 +87,14 @@ fn open_pause_menu(mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Pause);
 }
 
+fn open_inventory_menu(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Inventory);
+}
+
+fn open_journal_menu(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Journal);
+}
+
 fn close_menu(mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::None);
 }
@@ -2,11 +2,15 @@
 
 use bevy::{
     image::{ImageLoaderSettings, ImageSampler},
-    input::common_conditions::input_just_pressed,
     prelude::*,
 };
 
-use crate::{AppSystems, screens::Screen, theme::prelude::*};
+use crate::{
+    AppSystems,
+    input::{InputAction, action_just_pressed},
+    screens::Screen,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     // Spawn splash screen.
@@ -39,7 +43,7 @@ pub(super) fn plugin(app: &mut App) {
     app.add_systems(
         Update,
         enter_title_screen
-            .run_if(input_just_pressed(KeyCode::Escape).and(in_state(Screen::Splash))),
+            .run_if(action_just_pressed(InputAction::Back).and(in_state(Screen::Splash))),
     );
 }
 
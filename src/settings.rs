@@ -0,0 +1,231 @@
+//! Settings that persist between runs of the game.
+//!
+//! The settings are loaded while the app is being built, before any audio can
+//! play, and applied to the resources they control (e.g. [`GlobalVolume`]).
+//! Whenever one of those resources changes, the settings are saved again.
+//!
+//! To persist a new setting, add a field to [`Settings`] and copy it to and
+//! from its resource in [`Settings::apply`] and [`Settings::capture`]. Fields
+//! missing from older files fall back to their defaults, so this doesn't need
+//! a version bump. Bump [`SETTINGS_VERSION`] and handle it in
+//! [`Settings::migrate`] only when the meaning of an existing field changes.
+
+use std::collections::BTreeMap;
+
+use bevy::{audio::Volume, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audio::ChannelVolumes,
+    game::{
+        camera::CameraSmoothing, camera_shake::ReducedMotion, pixel_perfect::PixelPerfect,
+        player::MovementInputMode,
+    },
+    input::{ActionBindings, InputBindings},
+    storage,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let settings = Settings::load();
+    settings.apply(app.world_mut());
+    app.insert_resource(settings);
+
+    app.add_systems(
+        Last,
+        save_settings.run_if(
+            resource_changed::<GlobalVolume>
+                .or(resource_changed::<ChannelVolumes>)
+                .or(resource_changed::<MovementInputMode>)
+                .or(resource_changed::<CameraSmoothing>)
+                .or(resource_changed::<ReducedMotion>)
+                .or(resource_changed::<PixelPerfect>)
+                .or(resource_changed::<InputBindings>),
+        ),
+    );
+}
+
+/// The storage key the settings are saved under.
+const SETTINGS_KEY: &str = "settings.ron";
+
+/// The current version of the settings format.
+const SETTINGS_VERSION: u32 = 1;
+
+/// The persisted settings, as they were last loaded or saved.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub master_volume: f32,
+    pub channel_volumes: ChannelVolumes,
+    pub movement_input_mode: MovementInputMode,
+    pub camera_smoothing: CameraSmoothing,
+    pub reduced_motion: bool,
+    pub pixel_perfect: bool,
+    pub bindings: BTreeMap<String, ActionBindings>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            master_volume: 1.0,
+            channel_volumes: ChannelVolumes::default(),
+            movement_input_mode: MovementInputMode::default(),
+            camera_smoothing: CameraSmoothing::default(),
+            reduced_motion: false,
+            pixel_perfect: false,
+            bindings: InputBindings::default().to_saved(),
+        }
+    }
+}
+
+impl Settings {
+    /// Load the saved settings, falling back to the defaults if there are none
+    /// or they can't be read.
+    fn load() -> Self {
+        let contents = match storage::read(SETTINGS_KEY) {
+            Ok(Some(contents)) => contents,
+            Ok(None) => return Self::default(),
+            Err(error) => {
+                warn!("Failed to read settings: {error}");
+                return Self::default();
+            }
+        };
+        match ron::from_str::<Self>(&contents) {
+            Ok(settings) => settings.migrate(),
+            Err(error) => {
+                warn!("Failed to parse settings, using the defaults: {error}");
+                Self::default()
+            }
+        }
+    }
+
+    /// Upgrade settings saved by an older version of the game.
+    fn migrate(mut self) -> Self {
+        if self.version > SETTINGS_VERSION {
+            warn!(
+                "Settings were saved by a newer version of the game (version {}), \
+                 unknown settings will be ignored",
+                self.version
+            );
+        }
+        // No migrations yet. Match on `self.version` here when there are.
+        self.version = SETTINGS_VERSION;
+        self
+    }
+
+    fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                error!("Failed to serialize settings: {error}");
+                return;
+            }
+        };
+        if let Err(error) = storage::write(SETTINGS_KEY, &contents) {
+            warn!("Failed to save settings: {error}");
+        }
+    }
+
+    /// Apply the settings to the resources they control.
+    fn apply(&self, world: &mut World) {
+        world.insert_resource(GlobalVolume::new(Volume::Linear(self.master_volume)));
+        world.insert_resource(self.channel_volumes);
+        world.insert_resource(self.movement_input_mode);
+        world.insert_resource(self.camera_smoothing);
+        world.insert_resource(ReducedMotion(self.reduced_motion));
+        world.insert_resource(PixelPerfect(self.pixel_perfect));
+        world.insert_resource(InputBindings::from_saved(&self.bindings));
+    }
+
+    /// Read the settings back from the resources they control.
+    fn capture(
+        global_volume: &GlobalVolume,
+        channel_volumes: ChannelVolumes,
+        movement_input_mode: MovementInputMode,
+        camera_smoothing: CameraSmoothing,
+        reduced_motion: ReducedMotion,
+        pixel_perfect: PixelPerfect,
+        bindings: &InputBindings,
+    ) -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            master_volume: global_volume.volume.to_linear(),
+            channel_volumes,
+            movement_input_mode,
+            camera_smoothing,
+            reduced_motion: reduced_motion.0,
+            pixel_perfect: pixel_perfect.0,
+            bindings: bindings.to_saved(),
+        }
+    }
+}
+
+fn save_settings(
+    global_volume: Res<GlobalVolume>,
+    channel_volumes: Res<ChannelVolumes>,
+    movement_input_mode: Res<MovementInputMode>,
+    camera_smoothing: Res<CameraSmoothing>,
+    reduced_motion: Res<ReducedMotion>,
+    pixel_perfect: Res<PixelPerfect>,
+    bindings: Res<InputBindings>,
+    mut settings: ResMut<Settings>,
+) {
+    let current = Settings::capture(
+        &global_volume,
+        *channel_volumes,
+        *movement_input_mode,
+        *camera_smoothing,
+        *reduced_motion,
+        *pixel_perfect,
+        &bindings,
+    );
+    if *settings != current {
+        current.save();
+        *settings = current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Settings {
+        ron::from_str::<Settings>(contents).unwrap().migrate()
+    }
+
+    #[test]
+    fn saved_settings_load_back_the_same() {
+        let settings = Settings {
+            master_volume: 0.25,
+            ..default()
+        };
+        let contents = ron::ser::to_string_pretty(&settings, default()).unwrap();
+        assert_eq!(parse(&contents), settings);
+    }
+
+    #[test]
+    fn missing_settings_fall_back_to_their_defaults() {
+        assert_eq!(
+            parse("(master_volume: 0.5)"),
+            Settings {
+                master_volume: 0.5,
+                ..default()
+            }
+        );
+    }
+
+    #[test]
+    fn older_versions_are_upgraded() {
+        let settings = parse("(version: 0, master_volume: 0.5)");
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.master_volume, 0.5);
+    }
+
+    #[test]
+    fn newer_versions_keep_known_settings_and_ignore_unknown_ones() {
+        let settings = parse("(version: 99, master_volume: 0.5, new_setting: true)");
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.master_volume, 0.5);
+    }
+}
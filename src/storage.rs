@@ -0,0 +1,89 @@
+//! Persistent key-value storage for small text files like settings.
+//!
+//! On native platforms each key is a file in the platform config directory
+//! (e.g. `~/.config/mythara` on Linux). On the web, keys are stored in the
+//! browser's `localStorage`.
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("no storage location is available on this platform")]
+    Unavailable,
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("could not access storage: {0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(target_arch = "wasm32")]
+    #[error("could not access browser storage: {0}")]
+    Web(String),
+}
+
+/// Read the contents stored under `key`, or `None` if nothing has been stored yet.
+pub fn read(key: &str) -> Result<Option<String>, StorageError> {
+    platform::read(key)
+}
+
+/// Store `contents` under `key`, replacing any previous contents.
+pub fn write(key: &str, contents: &str) -> Result<(), StorageError> {
+    platform::write(key, contents)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::{fs, io::ErrorKind, path::PathBuf};
+
+    use super::StorageError;
+
+    fn path(key: &str) -> Result<PathBuf, StorageError> {
+        let dir = dirs::config_dir().ok_or(StorageError::Unavailable)?;
+        Ok(dir.join("mythara").join(key))
+    }
+
+    pub fn read(key: &str) -> Result<Option<String>, StorageError> {
+        match fs::read_to_string(path(key)?) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn write(key: &str, contents: &str) -> Result<(), StorageError> {
+        let path = path(key)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write to a temporary file first, so that a crash mid-write can't
+        // leave a truncated file behind.
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, contents)?;
+        fs::rename(temp, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use super::StorageError;
+
+    fn local_storage() -> Result<web_sys::Storage, StorageError> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or(StorageError::Unavailable)
+    }
+
+    fn storage_key(key: &str) -> String {
+        format!("mythara/{key}")
+    }
+
+    pub fn read(key: &str) -> Result<Option<String>, StorageError> {
+        local_storage()?
+            .get_item(&storage_key(key))
+            .map_err(|error| StorageError::Web(format!("{error:?}")))
+    }
+
+    pub fn write(key: &str, contents: &str) -> Result<(), StorageError> {
+        local_storage()?
+            .set_item(&storage_key(key), contents)
+            .map_err(|error| StorageError::Web(format!("{error:?}")))
+    }
+}
@@ -1,6 +1,10 @@
 use bevy::prelude::*;
 
-use crate::{asset_tracking::LoadResource, audio::sound_effect};
+use crate::{
+    asset_tracking::LoadResource,
+    audio::ui_sound,
+    theme::navigation::{Activate, FocusedButton},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(Update, apply_interaction_palette);
@@ -8,27 +12,40 @@ pub(super) fn plugin(app: &mut App) {
     app.load_resource::<InteractionAssets>();
     app.add_observer(play_on_hover_sound_effect);
     app.add_observer(play_on_click_sound_effect);
+    app.add_systems(
+        Update,
+        play_on_focus_sound_effect.run_if(resource_changed::<FocusedButton>),
+    );
 }
 
 /// Palette for widget interactions. Add this to an entity that supports
 /// [`Interaction`]s, such as a button, to change its [`BackgroundColor`] based
-/// on the current interaction state.
+/// on the current interaction state and whether it is the [`FocusedButton`].
 #[derive(Component, Debug, Reflect)]
 #[reflect(Component)]
 pub struct InteractionPalette {
     pub none: Color,
     pub hovered: Color,
+    pub focused: Color,
     pub pressed: Color,
 }
 
 fn apply_interaction_palette(
-    mut palette_query: Query<
-        (&Interaction, &InteractionPalette, &mut BackgroundColor),
-        Changed<Interaction>,
-    >,
+    focused: Res<FocusedButton>,
+    mut palette_query: Query<(
+        Entity,
+        Ref<Interaction>,
+        &InteractionPalette,
+        &mut BackgroundColor,
+    )>,
 ) {
-    for (interaction, palette, mut background) in &mut palette_query {
-        *background = match interaction {
+    for (entity, interaction, palette, mut background) in &mut palette_query {
+        if !interaction.is_changed() && !focused.is_changed() {
+            continue;
+        }
+
+        *background = match *interaction {
+            Interaction::None if focused.0 == Some(entity) => palette.focused,
             Interaction::None => palette.none,
             Interaction::Hovered => palette.hovered,
             Interaction::Pressed => palette.pressed,
@@ -67,12 +84,26 @@ fn play_on_hover_sound_effect(
     };
 
     if interaction_query.contains(trigger.entity) {
-        commands.spawn(sound_effect(interaction_assets.hover.clone()));
+        commands.spawn(ui_sound(interaction_assets.hover.clone()));
+    }
+}
+
+fn play_on_focus_sound_effect(
+    mut commands: Commands,
+    interaction_assets: Option<Res<InteractionAssets>>,
+    focused: Res<FocusedButton>,
+) {
+    let Some(interaction_assets) = interaction_assets else {
+        return;
+    };
+
+    if focused.0.is_some() {
+        commands.spawn(ui_sound(interaction_assets.hover.clone()));
     }
 }
 
 fn play_on_click_sound_effect(
-    trigger: On<Pointer<Click>>,
+    trigger: On<Activate>,
     mut commands: Commands,
     interaction_assets: Option<Res<InteractionAssets>>,
     interaction_query: Query<(), With<Interaction>>,
@@ -82,6 +113,6 @@ fn play_on_click_sound_effect(
     };
 
     if interaction_query.contains(trigger.entity) {
-        commands.spawn(sound_effect(interaction_assets.click.clone()));
+        commands.spawn(ui_sound(interaction_assets.click.clone()));
     }
 }
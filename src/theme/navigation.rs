@@ -0,0 +1,179 @@
+//! Focus-based navigation for [`Button`]s, so that menus can be used without
+//! a mouse.
+//!
+//! One button at a time can hold the [`FocusedButton`]. The focus is moved
+//! between visible buttons with the directional navigation actions (arrow keys
+//! or D-pad by default) or the left stick, and cycled in reading order with
+//! [`InputAction::NavigateNext`] (tab by default, backwards while holding
+//! shift). The focused button is activated with [`InputAction::Confirm`]. Both
+//! this and a pointer click trigger [`Activate`] on the button, so button
+//! actions should observe [`Activate`] rather than [`Pointer<Click>`].
+//!
+//! Pointing at a button with the mouse clears the focus, so that only one
+//! button looks highlighted at a time.
+
+use bevy::prelude::*;
+
+use crate::input::{ActionState, InputAction, left_stick};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<FocusedButton>();
+    app.add_observer(activate_on_click);
+    app.add_observer(clear_focus_on_hover);
+    app.add_systems(
+        Update,
+        (clear_lost_focus, navigate_focus, activate_focused_button).chain(),
+    );
+}
+
+/// Triggered on a button entity when it is clicked or activated while focused.
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct Activate {
+    pub entity: Entity,
+}
+
+/// The button that currently has navigation focus, if any.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct FocusedButton(pub Option<Entity>);
+
+/// How far the left stick has to be pushed to count as a navigation press.
+const STICK_NAVIGATION_THRESHOLD: f32 = 0.5;
+
+fn activate_on_click(
+    click: On<Pointer<Click>>,
+    mut commands: Commands,
+    button_query: Query<(), With<Button>>,
+) {
+    if button_query.contains(click.entity) {
+        commands.trigger(Activate {
+            entity: click.entity,
+        });
+    }
+}
+
+fn clear_focus_on_hover(
+    over: On<Pointer<Over>>,
+    button_query: Query<(), With<Button>>,
+    mut focused: ResMut<FocusedButton>,
+) {
+    if button_query.contains(over.entity) && focused.0.is_some() {
+        focused.0 = None;
+    }
+}
+
+/// Drop the focus once its button is despawned or hidden, e.g. when a menu closes.
+fn clear_lost_focus(
+    mut focused: ResMut<FocusedButton>,
+    button_query: Query<&InheritedVisibility, With<Button>>,
+) {
+    let Some(entity) = focused.0 else {
+        return;
+    };
+    if !button_query
+        .get(entity)
+        .is_ok_and(|visibility| visibility.get())
+    {
+        focused.0 = None;
+    }
+}
+
+fn navigate_focus(
+    actions: Res<ActionState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut stick_held: Local<bool>,
+    mut focused: ResMut<FocusedButton>,
+    button_query: Query<(Entity, &UiGlobalTransform, &InheritedVisibility), With<Button>>,
+) {
+    // UI coordinates grow downwards, so "up" is negative Y.
+    let mut direction = Vec2::ZERO;
+    if actions.just_pressed(InputAction::NavigateUp) {
+        direction.y -= 1.0;
+    }
+    if actions.just_pressed(InputAction::NavigateDown) {
+        direction.y += 1.0;
+    }
+    if actions.just_pressed(InputAction::NavigateLeft) {
+        direction.x -= 1.0;
+    }
+    if actions.just_pressed(InputAction::NavigateRight) {
+        direction.x += 1.0;
+    }
+
+    // The stick acts like a D-pad press along its dominant axis, and has to
+    // return to the center before it navigates again.
+    let stick = left_stick(&gamepads);
+    if stick.length() < STICK_NAVIGATION_THRESHOLD {
+        *stick_held = false;
+    } else if !*stick_held {
+        *stick_held = true;
+        direction = if stick.x.abs() > stick.y.abs() {
+            Vec2::new(stick.x.signum(), 0.0)
+        } else {
+            Vec2::new(0.0, -stick.y.signum())
+        };
+    }
+
+    let cycle = actions.just_pressed(InputAction::NavigateNext);
+    if direction == Vec2::ZERO && !cycle {
+        return;
+    }
+
+    let mut buttons = button_query
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get())
+        .map(|(entity, transform, _)| (entity, transform.translation))
+        .collect::<Vec<_>>();
+    buttons.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let current = focused
+        .0
+        .and_then(|entity| buttons.iter().position(|(button, _)| *button == entity));
+    let next = match current {
+        Some(index) if cycle => {
+            let backwards = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+            let step = if backwards { buttons.len() - 1 } else { 1 };
+            Some(buttons[(index + step) % buttons.len()].0)
+        }
+        Some(index) => nearest_in_direction(buttons[index].1, direction, &buttons),
+        // Start from the first button in reading order.
+        None => buttons.first().map(|(entity, _)| *entity),
+    };
+
+    if let Some(next) = next {
+        focused.0 = Some(next);
+    }
+}
+
+/// Find the button closest to `from` in the given direction, preferring buttons
+/// that are aligned with it over ones that are off to the side.
+fn nearest_in_direction(from: Vec2, direction: Vec2, buttons: &[(Entity, Vec2)]) -> Option<Entity> {
+    let direction = direction.normalize();
+    buttons
+        .iter()
+        .filter_map(|&(entity, position)| {
+            let offset = position - from;
+            let along = offset.dot(direction);
+            if along <= 0.0 {
+                return None;
+            }
+            let across = offset.perp_dot(direction).abs();
+            Some((entity, along + 2.0 * across))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+fn activate_focused_button(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    focused: Res<FocusedButton>,
+) {
+    let Some(entity) = focused.0 else {
+        return;
+    };
+    if actions.just_pressed(InputAction::Confirm) {
+        commands.trigger(Activate { entity });
+    }
+}
@@ -12,5 +12,7 @@ pub const BUTTON_TEXT: Color = Color::srgb(0.925, 0.925, 0.925);
 pub const BUTTON_BACKGROUND: Color = Color::srgb(0.275, 0.400, 0.750);
 /// #6299d1
 pub const BUTTON_HOVERED_BACKGROUND: Color = Color::srgb(0.384, 0.600, 0.820);
+/// #5580c8
+pub const BUTTON_FOCUSED_BACKGROUND: Color = Color::srgb(0.333, 0.502, 0.784);
 /// #3d4999
 pub const BUTTON_PRESSED_BACKGROUND: Color = Color::srgb(0.239, 0.286, 0.600);
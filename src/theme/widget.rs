@@ -48,6 +48,16 @@ pub fn label(text: impl Into<String>) -> impl Bundle {
     )
 }
 
+/// A text label in a smaller font, e.g. for descriptions beneath a [`label`].
+pub fn label_small(text: impl Into<String>) -> impl Bundle {
+    (
+        Name::new("Label"),
+        Text(text.into()),
+        TextFont::from_font_size(18.0),
+        TextColor(LABEL_TEXT),
+    )
+}
+
 /// A large rounded button with text and an action defined as an [`Observer`].
 pub fn button<E, B, M, I>(text: impl Into<String>, action: I) -> impl Bundle
 where
@@ -68,6 +78,57 @@ where
             },
             BorderRadius::MAX,
         ),
+        40.0,
+    )
+}
+
+/// A medium rounded button with text and an action defined as an [`Observer`].
+/// Fits in a row of [`label`]s.
+pub fn button_medium<E, B, M, I>(text: impl Into<String>, action: I) -> impl Bundle
+where
+    E: EntityEvent,
+    B: Bundle,
+    I: IntoObserverSystem<E, B, M>,
+{
+    button_base(
+        text,
+        action,
+        (
+            Node {
+                width: px(120),
+                height: px(32),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BorderRadius::MAX,
+        ),
+        24.0,
+    )
+}
+
+/// A wide rounded button with text and an action defined as an [`Observer`].
+/// Fits a line of text, e.g. a dialogue choice.
+pub fn button_wide<E, B, M, I>(text: impl Into<String>, action: I) -> impl Bundle
+where
+    E: EntityEvent,
+    B: Bundle,
+    I: IntoObserverSystem<E, B, M>,
+{
+    button_base(
+        text,
+        action,
+        (
+            Node {
+                width: px(640),
+                height: px(40),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BorderRadius::MAX,
+        ),
+        24.0,
     )
 }
 
@@ -88,6 +149,7 @@ where
             justify_content: JustifyContent::Center,
             ..default()
         },
+        40.0,
     )
 }
 
@@ -96,6 +158,7 @@ fn button_base<E, B, M, I>(
     text: impl Into<String>,
     action: I,
     button_bundle: impl Bundle,
+    font_size: f32,
 ) -> impl Bundle
 where
     E: EntityEvent,
@@ -107,7 +170,7 @@ where
     (
         Name::new("Button"),
         Node::default(),
-        Children::spawn(SpawnWith(|parent: &mut ChildSpawner| {
+        Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
             parent
                 .spawn((
                     Name::new("Button Inner"),
@@ -116,12 +179,13 @@ where
                     InteractionPalette {
                         none: BUTTON_BACKGROUND,
                         hovered: BUTTON_HOVERED_BACKGROUND,
+                        focused: BUTTON_FOCUSED_BACKGROUND,
                         pressed: BUTTON_PRESSED_BACKGROUND,
                     },
                     children![(
                         Name::new("Button Text"),
                         Text(text),
-                        TextFont::from_font_size(40.0),
+                        TextFont::from_font_size(font_size),
                         TextColor(BUTTON_TEXT),
                         // Don't bubble picking events from the text up to the button.
                         Pickable::IGNORE,